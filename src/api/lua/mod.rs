@@ -5,11 +5,11 @@ use rlua::{self};
 
 fn access_global_ns<'lua>(
     ctx: rlua::Context<'lua>,
-    ns: &Vec<String>,
+    ns: &[String],
 ) -> Result<(rlua::Table<'lua>, String)> {
     let mut last_head = ctx.globals();
     let mut head = rlua::Value::Nil;
-    for n in ns.iter().map(|n| AsRef::<str>::as_ref(n)) {
+    for n in ns.iter().map(AsRef::<str>::as_ref) {
         match head {
             rlua::Value::Table(t) => {
                 last_head = t.clone();
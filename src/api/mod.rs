@@ -3,7 +3,10 @@ use std::io::Read;
 use anyhow::{anyhow, Result};
 use clap::{builder::PossibleValue, ValueEnum};
 use rlua::{Lua, StdLib, Table};
-use serde::{de::Deserializer, Deserialize};
+use serde::{
+    de::{DeserializeOwned, Deserializer},
+    Deserialize,
+};
 
 mod lua;
 
@@ -15,6 +18,9 @@ use crate::{
 #[derive(Clone, Copy, Debug)]
 pub enum InputFormat {
     Yaml,
+    Json,
+    Json5,
+    Toml,
     Lua,
 }
 
@@ -35,7 +41,7 @@ fn deserialize_terminator<'de, D: Deserializer<'de>>(
     deserializer: D,
 ) -> Result<Option<csv::Terminator>, D::Error> {
     let s = Option::<u8>::deserialize(deserializer)?;
-    Ok(s.map(|s| csv::Terminator::Any(s)))
+    Ok(s.map(csv::Terminator::Any))
 }
 fn load_bindings<'lua>(ctx: rlua::Context<'lua>) -> Result<()> {
     let tbl = ctx.create_table()?;
@@ -66,7 +72,6 @@ fn load_bindings<'lua>(ctx: rlua::Context<'lua>) -> Result<()> {
                 Ok({
                     let records = p
                         .records()
-                        .into_iter()
                         .map(|r| {
                             r.map_err(|e| rlua::Error::RuntimeError(e.to_string()))
                                 .map(|r| r.into_iter().map(|s| s.to_owned()).collect())
@@ -98,45 +103,174 @@ impl InputFormat {
         p.extension().and_then(|p| match p.to_str()? {
             "lua" => Some(InputFormat::Lua),
             "yaml" | "yml" => Some(InputFormat::Yaml),
+            "json" => Some(InputFormat::Json),
+            "json5" => Some(InputFormat::Json5),
+            "toml" => Some(InputFormat::Toml),
             _ => None,
         })
     }
 }
 impl ValueEnum for InputFormat {
     fn value_variants<'a>() -> &'a [Self] {
-        &[InputFormat::Yaml, InputFormat::Lua]
+        &[
+            InputFormat::Yaml,
+            InputFormat::Json,
+            InputFormat::Json5,
+            InputFormat::Toml,
+            InputFormat::Lua,
+        ]
     }
 
     fn to_possible_value(&self) -> Option<clap::builder::PossibleValue> {
         Some(match self {
             InputFormat::Yaml => PossibleValue::new("yaml"),
+            InputFormat::Json => PossibleValue::new("json"),
+            InputFormat::Json5 => PossibleValue::new("json5"),
+            InputFormat::Toml => PossibleValue::new("toml"),
             InputFormat::Lua => PossibleValue::new("lua"),
         })
     }
 }
 
-pub fn load_chart<F: Read>(f: &mut F, fmt: InputFormat) -> Result<Charts> {
+/// How the charts are given in a spec
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Shape {
+    /// The spec is a single chart
+    One,
+    /// The spec is a list of charts
+    List,
+    /// The spec is a map with a list of charts under `pages`
+    Pages,
+}
+
+#[derive(Deserialize)]
+struct Pages {
+    pages: Vec<Charts>,
+}
+
+/// Error from deserializing the input, `at` is the line and column the error is at
+fn input_error(e: impl std::fmt::Display, at: Option<(usize, usize)>) -> anyhow::Error {
+    anyhow!(
+        "failed to deserailize input: {} at {}",
+        e,
+        at.map(|(line, column)| format!("{}:{}", line, column))
+            .unwrap_or("unknown".to_owned())
+    )
+}
+
+/// Deserialize `buf` in any of the formats besides lua
+fn parse<T: DeserializeOwned>(buf: &str, fmt: InputFormat) -> Result<T> {
     match fmt {
-        InputFormat::Yaml => Ok(serde_yaml::from_reader(f).map_err(|e| {
-            anyhow!(
-                "failed to deserailize input: {} at {}",
-                e.to_string(),
-                e.location()
-                    .map(|l| format!("{}:{}", l.line(), l.column()))
-                    .unwrap_or("unknown".to_owned())
-            )
-        })?),
+        InputFormat::Yaml => serde_yaml::from_str(buf).map_err(|e| {
+            let at = e.location().map(|l| (l.line(), l.column()));
+            input_error(e, at)
+        }),
+        // serde_json gives line 0 for errors without a position
+        InputFormat::Json => serde_json::from_str(buf)
+            .map_err(|e| input_error(&e, (e.line() > 0).then(|| (e.line(), e.column())))),
+        InputFormat::Json5 => json5::from_str(buf).map_err(|e| {
+            let json5::Error::Message { msg, location } = e;
+            input_error(msg, location.map(|l| (l.line, l.column)))
+        }),
+        // toml counts lines and columns from 0
+        InputFormat::Toml => toml::from_str(buf)
+            .map_err(|e| input_error(&e, e.line_col().map(|(l, c)| (l + 1, c + 1)))),
+        InputFormat::Lua => unreachable!("lua specs are scripts which are run, not parsed"),
+    }
+}
+
+/// Load the charts in a spec, each of which goes on its own page
+pub fn load_charts<F: Read>(f: &mut F, fmt: InputFormat) -> Result<Vec<Charts>> {
+    let charts = match fmt {
+        InputFormat::Yaml | InputFormat::Json | InputFormat::Json5 | InputFormat::Toml => {
+            let mut buf = String::new();
+            f.read_to_string(&mut buf)?;
+            // parsed twice so errors in the charts still have their line and column
+            let shape = match parse(&buf, fmt)? {
+                serde_json::Value::Array(_) => Shape::List,
+                serde_json::Value::Object(m)
+                    if m.contains_key("pages") && !m.contains_key("type") =>
+                {
+                    Shape::Pages
+                }
+                _ => Shape::One,
+            };
+            match shape {
+                Shape::One => vec![parse(&buf, fmt)?],
+                Shape::List => parse(&buf, fmt)?,
+                Shape::Pages => parse::<Pages>(&buf, fmt)?.pages,
+            }
+        }
         InputFormat::Lua => {
             let mut buf = Vec::new();
             f.read_to_end(&mut buf)?;
             let lua = Lua::new();
             lua.load_from_std_lib(StdLib::ALL_NO_DEBUG)?;
-            lua.context(|c| {
+            lua.context(|c| -> Result<_> {
                 load_bindings(c)?;
                 lua::load_api(c)?;
-                let chunk = c.load(&buf);
-                Ok(from_lua(chunk.eval()?)?)
-            })
+                let v: rlua::Value = c.load(&buf).eval()?;
+                let shape = match &v {
+                    rlua::Value::Table(t)
+                        if t.contains_key("pages")? && !t.contains_key("type")? =>
+                    {
+                        Shape::Pages
+                    }
+                    rlua::Value::Table(t) if t.raw_len() > 0 => Shape::List,
+                    _ => Shape::One,
+                };
+                Ok(match shape {
+                    Shape::One => vec![from_lua(v)?],
+                    Shape::List => from_lua(v)?,
+                    Shape::Pages => from_lua::<Pages>(v)?.pages,
+                })
+            })?
         }
+    };
+    if charts.is_empty() {
+        return Err(anyhow!("spec has no charts"));
+    }
+    Ok(charts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_charts() {
+        let pie = "{type: pie, categories: [a], datasets: [{name: a, values: [1]}]}";
+        let load = |spec: &str| load_charts(&mut spec.as_bytes(), InputFormat::Yaml);
+        assert_eq!(load(pie).unwrap().len(), 1);
+        assert_eq!(load(&format!("[{}, {}]", pie, pie)).unwrap().len(), 2);
+        assert_eq!(
+            load(&format!("pages: [{}, {}]", pie, pie)).unwrap().len(),
+            2
+        );
+        assert!(load("[]").is_err());
+        let load_as = |spec: &str, fmt| load_charts(&mut spec.as_bytes(), fmt);
+        let json =
+            r#"{"type": "pie", "categories": ["a"], "datasets": [{"name": "a", "values": [1]}]}"#;
+        assert_eq!(load_as(json, InputFormat::Json).unwrap().len(), 1);
+        let json5 = "{type: 'pie', categories: ['a'], datasets: [{name: 'a', values: [1],},]}";
+        assert_eq!(load_as(json5, InputFormat::Json5).unwrap().len(), 1);
+        let toml = "[[pages]]\ntype = 'pie'\ncategories = ['a']\ndatasets = [{name = 'a', values = [1]}]\n";
+        assert_eq!(load_as(toml, InputFormat::Toml).unwrap().len(), 1);
+        let err = load_as(
+            "{\n  \"type\": \"pie\",\n  \"categories\" [\"a\"]\n}",
+            InputFormat::Json,
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.ends_with("at 3:16"), "{}", err);
+        let pie =
+            "{ type = 'pie', categories = { 'a' }, datasets = { { name = 'a', values = { 1 } } } }";
+        let lua = format!("return {{ pages = {{ {}, {} }} }}", pie, pie);
+        assert_eq!(
+            load_charts(&mut lua.as_bytes(), InputFormat::Lua)
+                .unwrap()
+                .len(),
+            2
+        );
     }
 }
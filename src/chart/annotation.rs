@@ -0,0 +1,315 @@
+use std::fmt::Display;
+
+use plotters::coord::types::RangedCoordf64;
+use plotters::element::{EmptyElement, PathElement, Rectangle, Text};
+use plotters::prelude::{Cartesian2d, ChartContext, DrawingBackend, Ranged};
+use plotters::style::text_anchor::{HPos, Pos, VPos};
+use plotters::style::{Color, RGBAColor, TextStyle};
+use serde::Deserialize;
+
+use crate::render::{self, CssColour, FontInfo};
+
+/// Position along an axis as it appears in the spec
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+pub enum AxisValue {
+    Number(f64),
+    /// A category or row name, or a date/time on a datetime axis
+    Text(String),
+}
+
+impl Display for AxisValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AxisValue::Number(v) => write!(f, "{}", v),
+            AxisValue::Text(s) => write!(f, "'{}'", s),
+        }
+    }
+}
+
+/// Axis that annotations can be placed on
+pub trait AnnotationAxis: Ranged {
+    /// Where `v` is on the axis, `None` if it isn't a value the axis has
+    fn position(&self, v: &AxisValue) -> Option<Self::ValueType>;
+}
+
+/// Pie charts are drawn from -1 to 1 on both axes with the centre at 0
+impl AnnotationAxis for RangedCoordf64 {
+    fn position(&self, v: &AxisValue) -> Option<f64> {
+        match v {
+            AxisValue::Number(v) => Some(*v),
+            AxisValue::Text(_) => None,
+        }
+    }
+}
+
+/// Something drawn over the datasets, positioned using the chart's axes
+#[derive(Clone, Debug, Deserialize)]
+pub struct Annotation {
+    #[serde(flatten)]
+    kind: AnnotationKind,
+    /// default: the theme's text colour, ranges are filled with it at the range's opacity
+    #[serde(alias = "color")]
+    colour: Option<CssColour>,
+    /// Width of lines in pixels, default: 1
+    width: Option<f64>,
+    /// Font of any text, default: the chart's font
+    font: Option<FontInfo>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "type")]
+pub enum AnnotationKind {
+    /// Line across the whole chart at a y value
+    #[serde(rename = "hline")]
+    HLine {
+        y: AxisValue,
+        /// Text at the right end of the line
+        label: Option<String>,
+    },
+    /// Line up the whole chart at an x value
+    #[serde(rename = "vline")]
+    VLine {
+        x: AxisValue,
+        /// Text at the top of the line
+        label: Option<String>,
+    },
+    /// Shaded band up the whole chart between two x values
+    #[serde(rename = "x-range")]
+    XRange {
+        from: AxisValue,
+        to: AxisValue,
+        /// Text in the top left of the band
+        label: Option<String>,
+        /// Opacity of the band, default: 0.2
+        opacity: Option<f64>,
+    },
+    /// Shaded band across the whole chart between two y values
+    #[serde(rename = "y-range")]
+    YRange {
+        from: AxisValue,
+        to: AxisValue,
+        /// Text in the top left of the band
+        label: Option<String>,
+        /// Opacity of the band, default: 0.2
+        opacity: Option<f64>,
+    },
+    /// Text centred on a point
+    #[serde(rename = "text")]
+    Text {
+        x: AxisValue,
+        y: AxisValue,
+        text: String,
+    },
+    /// Arrow pointing at a point, with optional text at its tail
+    #[serde(rename = "arrow")]
+    Arrow {
+        x: AxisValue,
+        y: AxisValue,
+        text: Option<String>,
+        /// Offset of the tail from the point in pixels, default: 30
+        dx: Option<f64>,
+        /// Offset of the tail from the point in pixels, down is positive. default: -30
+        dy: Option<f64>,
+    },
+}
+
+/// Gap in pixels between a line or range and its label
+const LABEL_GAP: i32 = 3;
+/// Length of the sides of an arrow head in pixels
+const ARROW_HEAD: f64 = 8.0;
+
+fn position<A: AnnotationAxis>(
+    axis: &A,
+    v: &AxisValue,
+    name: &str,
+) -> Result<A::ValueType, render::Error> {
+    axis.position(v).ok_or_else(|| {
+        render::Error::InvalidChart(format!("annotation {} of {} is not on the axis", name, v))
+    })
+}
+
+impl Annotation {
+    fn colour(&self, default: RGBAColor) -> RGBAColor {
+        self.colour.map(|c| c.as_rgba()).unwrap_or(default)
+    }
+
+    /// Draw on `chart`, `text` is the chart's text style which is used unless the annotation has
+    /// its own font or colour
+    pub fn draw<DB: DrawingBackend, X: AnnotationAxis, Y: AnnotationAxis>(
+        &self,
+        chart: &ChartContext<DB, Cartesian2d<X, Y>>,
+        text: &TextStyle,
+    ) -> Result<(), render::Error> {
+        let colour = self.colour(render::text_colour(text));
+        let line = || colour.stroke_width(self.width.unwrap_or(1.0).round() as u32);
+        let tfont = match &self.font {
+            Some(font) => font.to_text_style(),
+            None => text.clone(),
+        }
+        .color(&colour);
+        let (xs, ys) = (
+            chart.as_coord_spec().x_spec(),
+            chart.as_coord_spec().y_spec(),
+        );
+        let area = chart.plotting_area();
+        match &self.kind {
+            AnnotationKind::HLine { y, label, .. } => {
+                let at = || position(ys, y, "y");
+                area.draw(&PathElement::new(
+                    vec![(xs.range().start, at()?), (xs.range().end, at()?)],
+                    line(),
+                ))?;
+                if let Some(label) = label {
+                    area.draw(
+                        &(EmptyElement::at((xs.range().end, at()?))
+                            + Text::new(
+                                label.clone(),
+                                (-LABEL_GAP, -LABEL_GAP),
+                                tfont.pos(Pos::new(HPos::Right, VPos::Bottom)),
+                            )),
+                    )?;
+                }
+            }
+            AnnotationKind::VLine { x, label, .. } => {
+                let at = || position(xs, x, "x");
+                area.draw(&PathElement::new(
+                    vec![(at()?, ys.range().start), (at()?, ys.range().end)],
+                    line(),
+                ))?;
+                if let Some(label) = label {
+                    area.draw(
+                        &(EmptyElement::at((at()?, ys.range().end))
+                            + Text::new(
+                                label.clone(),
+                                (LABEL_GAP, LABEL_GAP),
+                                tfont.pos(Pos::new(HPos::Left, VPos::Top)),
+                            )),
+                    )?;
+                }
+            }
+            AnnotationKind::XRange {
+                from,
+                to,
+                label,
+                opacity,
+                ..
+            } => {
+                area.draw(&Rectangle::new(
+                    [
+                        (position(xs, from, "from")?, ys.range().start),
+                        (position(xs, to, "to")?, ys.range().end),
+                    ],
+                    colour.mix(opacity.unwrap_or(0.2)).filled(),
+                ))?;
+                if let Some(label) = label {
+                    area.draw(
+                        &(EmptyElement::at((position(xs, from, "from")?, ys.range().end))
+                            + Text::new(
+                                label.clone(),
+                                (LABEL_GAP, LABEL_GAP),
+                                tfont.pos(Pos::new(HPos::Left, VPos::Top)),
+                            )),
+                    )?;
+                }
+            }
+            AnnotationKind::YRange {
+                from,
+                to,
+                label,
+                opacity,
+                ..
+            } => {
+                area.draw(&Rectangle::new(
+                    [
+                        (xs.range().start, position(ys, from, "from")?),
+                        (xs.range().end, position(ys, to, "to")?),
+                    ],
+                    colour.mix(opacity.unwrap_or(0.2)).filled(),
+                ))?;
+                if let Some(label) = label {
+                    area.draw(
+                        &(EmptyElement::at((xs.range().start, position(ys, to, "to")?))
+                            + Text::new(
+                                label.clone(),
+                                (LABEL_GAP, LABEL_GAP),
+                                tfont.pos(Pos::new(HPos::Left, VPos::Top)),
+                            )),
+                    )?;
+                }
+            }
+            AnnotationKind::Text { x, y, text, .. } => {
+                area.draw(&Text::new(
+                    text.clone(),
+                    (position(xs, x, "x")?, position(ys, y, "y")?),
+                    tfont.pos(Pos::new(HPos::Center, VPos::Center)),
+                ))?;
+            }
+            AnnotationKind::Arrow {
+                x, y, text, dx, dy, ..
+            } => {
+                let (dx, dy) = (dx.unwrap_or(30.0), dy.unwrap_or(-30.0));
+                let tail = (dx.round() as i32, dy.round() as i32);
+                // head is two short lines back along the shaft, either side of it
+                let angle = dy.atan2(dx);
+                let side = |turn: f64| {
+                    let a = angle + turn;
+                    (
+                        (ARROW_HEAD * a.cos()).round() as i32,
+                        (ARROW_HEAD * a.sin()).round() as i32,
+                    )
+                };
+                let arrow = EmptyElement::at((position(xs, x, "x")?, position(ys, y, "y")?))
+                    + PathElement::new(vec![tail, (0, 0)], line())
+                    + PathElement::new(vec![side(0.4), (0, 0), side(-0.4)], line());
+                match text {
+                    Some(text) => {
+                        let hpos = match dx {
+                            dx if dx > 0.0 => HPos::Left,
+                            dx if dx < 0.0 => HPos::Right,
+                            _ => HPos::Center,
+                        };
+                        let vpos = if dy > 0.0 { VPos::Top } else { VPos::Bottom };
+                        area.draw(
+                            &(arrow
+                                + Text::new(text.clone(), tail, tfont.pos(Pos::new(hpos, vpos)))),
+                        )?;
+                    }
+                    None => area.draw(&arrow)?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use plotters::style::BLACK;
+
+    #[test]
+    fn test_annotation_spec() {
+        let a: Vec<Annotation> = serde_yaml::from_str(
+            "
+- {type: hline, y: 200, label: SLO, color: red}
+- {type: x-range, from: '2024-01-01', to: '2024-01-02'}
+- {type: arrow, x: b, y: 3, text: peak}
+",
+        )
+        .unwrap();
+        assert!(matches!(
+            &a[0].kind,
+            AnnotationKind::HLine { y: AxisValue::Number(y), label: Some(l), .. } if *y == 200.0 && l == "SLO"
+        ));
+        assert_eq!(a[0].colour(BLACK.to_rgba()), RGBAColor(255, 0, 0, 1.0));
+        assert!(matches!(
+            &a[1].kind,
+            AnnotationKind::XRange {
+                from: AxisValue::Text(_),
+                ..
+            }
+        ));
+        assert_eq!(a[2].colour(BLACK.to_rgba()), BLACK.to_rgba());
+    }
+}
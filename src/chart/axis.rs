@@ -0,0 +1,188 @@
+use std::ops::Range;
+
+use serde::Deserialize;
+
+use super::scale::{Scale, ScaledAxis};
+use crate::render;
+
+/// How many ticks an axis has, or exactly where they go
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum Ticks {
+    /// Most ticks to draw, they are placed at round numbers
+    Count(usize),
+    /// Draw a tick at each of these values
+    At(Vec<f64>),
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct TickFormat {
+    /// Number of decimal places, default: as many as needed
+    precision: Option<usize>,
+    /// Separate thousands with commas? default: false
+    thousands: Option<bool>,
+    /// Text before each label, e.g. `$`
+    prefix: Option<String>,
+    /// Text after each label, e.g. `%` or `ms`
+    suffix: Option<String>,
+}
+
+impl TickFormat {
+    /// Label for `v`, `default` is how it would be labelled otherwise
+    pub fn format(&self, v: f64, default: String) -> String {
+        let mut s = match self.precision {
+            Some(p) => format!("{:.*}", p, v),
+            None => default,
+        };
+        if self.thousands.unwrap_or(false) {
+            s = separate_thousands(&s);
+        }
+        // the prefix goes after the sign, -$1,000 rather than $-1,000
+        let (sign, s) = match s.strip_prefix('-') {
+            Some(rest) => ("-", rest),
+            None => ("", s.as_str()),
+        };
+        format!(
+            "{}{}{}{}",
+            sign,
+            self.prefix.as_deref().unwrap_or(""),
+            s,
+            self.suffix.as_deref().unwrap_or("")
+        )
+    }
+}
+
+/// Add commas between each group of three digits at the start of `s`
+fn separate_thousands(s: &str) -> String {
+    let start = s.find(|c: char| c.is_ascii_digit()).unwrap_or(s.len());
+    let len = s[start..]
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(s.len() - start);
+    let digits = &s[start..start + len];
+    let mut out = s[..start].to_owned();
+    for (n, c) in digits.chars().enumerate() {
+        if n > 0 && (len - n) % 3 == 0 {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out + &s[start + len..]
+}
+
+/// Options for a numeric axis
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct AxisOptions {
+    /// Lowest value on the axis, default: from the data
+    min: Option<f64>,
+    /// Highest value on the axis, default: from the data
+    max: Option<f64>,
+    /// Space around the data as a percentage of its range. Not added past zero if the data
+    /// starts or ends there
+    padding: Option<f64>,
+    /// Number of ticks or where to put them, default: picked from the range
+    ticks: Option<Ticks>,
+    /// How to write the tick labels
+    tick_format: Option<TickFormat>,
+}
+
+impl AxisOptions {
+    pub fn ticks(&self) -> Option<&Ticks> {
+        self.ticks.as_ref()
+    }
+
+    pub fn tick_format(&self) -> Option<&TickFormat> {
+        self.tick_format.as_ref()
+    }
+
+    /// These options with the axis fixed to `range`
+    pub fn with_range(self, range: Range<f64>) -> Self {
+        Self {
+            min: Some(range.start),
+            max: Some(range.end),
+            ..self
+        }
+    }
+
+    /// Range of the axis for data covering `data`. `padding` is the percentage used if the spec
+    /// doesn't give one
+    pub fn range(
+        &self,
+        scale: Scale,
+        data: Range<f64>,
+        padding: f64,
+    ) -> Result<Range<f64>, render::Error> {
+        let (low, high) = (scale.transform(data.start), scale.transform(data.end));
+        let pad = (high - low) * self.padding.unwrap_or(padding) / 100.0;
+        let pad_from = |v: f64, by: f64| if v == 0.0 { v } else { scale.inverse(by) };
+        let start = self.min.unwrap_or_else(|| pad_from(data.start, low - pad));
+        let end = self.max.unwrap_or_else(|| pad_from(data.end, high + pad));
+        scale.check(start, || "axis min".to_owned())?;
+        if start >= end {
+            return Err(render::Error::InvalidChart(format!(
+                "axis goes from {} to {}, the min must be less than the max",
+                start, end
+            )));
+        }
+        Ok(start..end)
+    }
+
+    /// Axis covering `data` with these options
+    pub fn scaled(
+        &self,
+        scale: Scale,
+        data: Range<f64>,
+        padding: f64,
+    ) -> Result<ScaledAxis, render::Error> {
+        Ok(ScaledAxis::new(scale, self.range(scale, data, padding)?).with_options(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn opts(yaml: &str) -> AxisOptions {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn test_axis_range() {
+        let none = AxisOptions::default();
+        assert_eq!(
+            none.range(Scale::Linear, 0.0..10.0, 0.0).unwrap(),
+            0.0..10.0
+        );
+        let padded = opts("padding: 10");
+        assert_eq!(
+            padded.range(Scale::Linear, 0.0..10.0, 0.0).unwrap(),
+            0.0..11.0
+        );
+        assert_eq!(
+            padded.range(Scale::Linear, -10.0..10.0, 0.0).unwrap(),
+            -12.0..12.0
+        );
+        assert_eq!(
+            padded.range(Scale::Log, 1.0..100.0, 0.0).unwrap(),
+            10f64.powf(-0.2)..10f64.powf(2.2)
+        );
+        let fixed = opts("{min: -1, max: 5, padding: 50}");
+        assert_eq!(
+            fixed.range(Scale::Linear, 0.0..10.0, 0.0).unwrap(),
+            -1.0..5.0
+        );
+        assert!(fixed.range(Scale::Log, 1.0..10.0, 0.0).is_err());
+        assert!(opts("min: 20")
+            .range(Scale::Linear, 0.0..10.0, 0.0)
+            .is_err());
+    }
+
+    #[test]
+    fn test_tick_format() {
+        let f: TickFormat = serde_yaml::from_str("{precision: 2, suffix: ms}").unwrap();
+        assert_eq!(f.format(1.5, "1.5".to_owned()), "1.50ms");
+        let f: TickFormat = serde_yaml::from_str("{thousands: true, prefix: $}").unwrap();
+        assert_eq!(f.format(1234567.5, "1234567.5".to_owned()), "$1,234,567.5");
+        assert_eq!(f.format(-1000.0, "-1000".to_owned()), "-$1,000");
+        assert_eq!(f.format(999.0, "999".to_owned()), "$999");
+    }
+}
@@ -3,29 +3,104 @@ use std::ops::Range;
 use plotters::coord::ranged1d::{NoDefaultFormatting, ValueFormatter};
 use plotters::element::Drawable;
 use plotters::prelude::{Cartesian2d, ChartContext, Ranged};
-use plotters::style::{FontFamily, TextStyle};
+use plotters::style::TextStyle;
 use plotters::{
-    prelude::{ChartBuilder, DrawingBackend, Rectangle},
-    style::{Color, ShapeStyle, WHITE},
+    prelude::{ChartBuilder, DrawingBackend, LabelAreaPosition, Rectangle},
+    style::{Color, ShapeStyle},
 };
 use serde::Deserialize;
 
 use super::{legend_for, Result};
+use crate::chart::annotation::{AnnotationAxis, AxisValue};
+use crate::chart::axis::AxisOptions;
+use crate::chart::error_bar::{ErrorBar, Whisker};
+use crate::chart::legend::LegendEntry;
+use crate::chart::scale::{span, Scale, ScaledAxis};
+use crate::chart::value_label::{LabelPosition, ValueLabels};
 use crate::chart::{ChartInfo, ChartType, Dataset};
-use crate::palette::colours;
+use crate::render;
+
+/// Width of the caps on error bars in pixels
+const ERROR_CAP_WIDTH: i32 = 8;
+
+/// Value of a bar, either a plain number or with its error
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+pub enum BarPoint {
+    Value(f64),
+    WithError {
+        value: f64,
+        #[serde(flatten)]
+        error: ErrorBar,
+    },
+}
+
+impl BarPoint {
+    fn value(&self) -> f64 {
+        match self {
+            BarPoint::Value(v) | BarPoint::WithError { value: v, .. } => *v,
+        }
+    }
+
+    fn error(&self) -> Option<&ErrorBar> {
+        match self {
+            BarPoint::Value(_) => None,
+            BarPoint::WithError { error, .. } => Some(error),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BarMode {
+    /// One bar per dataset, side by side within each category
+    #[default]
+    Grouped,
+    /// Datasets stacked on top of each other within each category
+    Stacked,
+    /// Like stacked, but each category is scaled to 100%
+    Percent,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Orientation {
+    /// Categories along the x axis, bars grow upwards
+    #[default]
+    Vertical,
+    /// Categories along the y axis, bars grow to the right
+    Horizontal,
+}
+
+/// Options for each dataset of a bar chart
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct BarDataset {
+    /// Print the values on the bars, default: the chart's labels
+    labels: Option<ValueLabels>,
+}
 
-pub type BarPoint = f64;
 #[derive(Clone, Debug, Deserialize)]
 pub struct BarChart {
     /// Spacing between block groups
     spacing: Option<f64>,
-    /// Categories of blocks, appear along the x axis
+    /// Categories of blocks, appear along the x axis (y axis if horizontal)
     categories: Vec<String>,
-    /// Draw grid lines for the x axis? default: true
+    /// Draw grid lines for the value axis? default: true
     lines: Option<bool>,
-    /// Label for the y axis
+    /// Label for the value axis
     y_label: Option<String>,
+    /// Scale of the value axis, default: linear
+    y_scale: Option<Scale>,
+    /// Range and ticks of the value axis
+    y_axis: Option<AxisOptions>,
+    /// How datasets are laid out within a category, default: grouped
+    mode: Option<BarMode>,
+    /// Which way the bars point, default: vertical
+    orientation: Option<Orientation>,
+    /// Print the values on the bars of every dataset, default: none
+    labels: Option<ValueLabels>,
 }
+#[allow(unused)]
 trait ToElement<T: Drawable<DB>, DB: DrawingBackend> {
     fn to_element<S: Into<ShapeStyle>>(self, style: S) -> T;
 }
@@ -50,6 +125,11 @@ impl BarChart {
             categories: Vec::default(),
             lines: None,
             y_label: None,
+            y_scale: None,
+            y_axis: None,
+            mode: None,
+            orientation: None,
+            labels: None,
         }
     }
     fn lines(&self) -> bool {
@@ -59,6 +139,164 @@ impl BarChart {
     fn spacing(&self) -> f64 {
         self.spacing.to_owned().unwrap_or(5.0)
     }
+
+    fn mode(&self) -> BarMode {
+        self.mode.unwrap_or_default()
+    }
+
+    fn orientation(&self) -> Orientation {
+        self.orientation.unwrap_or_default()
+    }
+
+    fn y_scale(&self) -> Scale {
+        self.y_scale.unwrap_or_default()
+    }
+
+    fn y_axis(&self) -> AxisOptions {
+        self.y_axis.clone().unwrap_or_default()
+    }
+
+    /// Labels to print on the bars of a dataset, if any
+    fn labels<'a>(&'a self, opts: &'a BarDataset) -> Option<&'a ValueLabels> {
+        opts.labels
+            .as_ref()
+            .or(self.labels.as_ref())
+            .filter(|l| l.show())
+    }
+
+    /// Pixel direction bars grow in for a value
+    fn direction(&self, v: f64) -> (f64, f64) {
+        let sign = if v < 0.0 { -1.0 } else { 1.0 };
+        match self.orientation() {
+            Orientation::Vertical => (0.0, -sign),
+            Orientation::Horizontal => (sign, 0.0),
+        }
+    }
+
+    /// Chart coordinate for a point on the category axis and a value
+    fn coord(&self, seg: BarSegment, v: f64) -> (BarValue, BarValue) {
+        match self.orientation() {
+            Orientation::Vertical => (BarValue::Segment(seg), BarValue::Value(v)),
+            Orientation::Horizontal => (BarValue::Value(v), BarValue::Segment(seg)),
+        }
+    }
+
+    /// Bottom and top of each bar, indexed by `[dataset][category]`. Bars go from zero in
+    /// whichever direction their value is, when stacking the positive and negative values are
+    /// stacked separately
+    fn extents(&self, datasets: &[Dataset<BarPoint, BarDataset>]) -> Vec<Vec<(f64, f64)>> {
+        let ncats = self.categories.len();
+        match self.mode() {
+            BarMode::Grouped => datasets
+                .iter()
+                .map(|dset| {
+                    dset.values
+                        .iter()
+                        .take(ncats)
+                        .map(|v| (v.value().min(0.0), v.value().max(0.0)))
+                        .collect()
+                })
+                .collect(),
+            mode => {
+                let totals = category_totals(datasets, ncats);
+                let mut above = vec![0.0; ncats];
+                let mut below = vec![0.0; ncats];
+                datasets
+                    .iter()
+                    .map(|dset| {
+                        (0..ncats)
+                            .map(|ncat| {
+                                let v = dset.values.get(ncat).map_or(0.0, BarPoint::value)
+                                    * self.value_factor(mode, totals[ncat]);
+                                if v < 0.0 {
+                                    let high = below[ncat];
+                                    below[ncat] += v;
+                                    (below[ncat], high)
+                                } else {
+                                    let low = above[ncat];
+                                    above[ncat] += v;
+                                    (low, above[ncat])
+                                }
+                            })
+                            .collect()
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// Multiplier taking a value to its size on the chart, for a category with `total`
+    fn value_factor(&self, mode: BarMode, total: f64) -> f64 {
+        if mode == BarMode::Percent && total != 0.0 {
+            100.0 / total
+        } else {
+            1.0
+        }
+    }
+
+    /// Low and high ends of the error of each bar that has one, around the end of the bar
+    /// furthest from zero. Indexed the same as the extents
+    #[allow(clippy::type_complexity)]
+    fn error_extents(
+        &self,
+        datasets: &[Dataset<BarPoint, BarDataset>],
+        extents: &[Vec<(f64, f64)>],
+    ) -> Result<Vec<Vec<Option<(f64, f64)>>>> {
+        let totals = category_totals(datasets, self.categories.len());
+        datasets
+            .iter()
+            .zip(extents)
+            .map(|(dset, ext)| {
+                dset.values
+                    .iter()
+                    .zip(ext)
+                    .zip(&totals)
+                    .enumerate()
+                    .map(|(ncat, ((v, (low, high)), total))| {
+                        let amounts = match v.error() {
+                            Some(e) => e.amounts(|| {
+                                format!("value {} in dataset '{}'", ncat, dset.extra.name)
+                            })?,
+                            None => None,
+                        };
+                        let end = if v.value() < 0.0 { *low } else { *high };
+                        let factor = self.value_factor(self.mode(), *total);
+                        Ok(amounts.map(|(l, h)| (end - l * factor, end + h * factor)))
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Range of the value axis needed to fit the bars with the given extents
+    fn value_range(&self, extents: &[Vec<(f64, f64)>]) -> Range<f64> {
+        let ends = || {
+            extents
+                .iter()
+                .flatten()
+                .flat_map(|(low, high)| [*low, *high])
+        };
+        match (self.y_scale(), self.mode()) {
+            // bars start from zero, which a log scale can't show so they start at the decade
+            // below the smallest bar instead
+            (Scale::Log, _) => {
+                let min = extents
+                    .iter()
+                    .flatten()
+                    .map(|(_, high)| *high)
+                    .fold(f64::INFINITY, f64::min);
+                let r = span(ends());
+                10f64.powf(min.log10().floor())..r.end
+            }
+            (_, BarMode::Percent) => {
+                let r = span(ends().chain([0.0]));
+                let low = if r.start < 0.0 { -100.0 } else { 0.0 };
+                let high = if r.end > 0.0 { 100.0 } else { 0.0 };
+                low..high
+            }
+            _ => span(ends().chain([0.0])),
+        }
+    }
 }
 
 pub enum BarSegment {
@@ -71,6 +309,12 @@ pub enum BarSegment {
     Center {
         cat: u64,
     },
+    /// fraction of the way across a block, for drawing inside it
+    Inner {
+        cat: u64,
+        num: u64,
+        frac: f64,
+    },
     End,
 }
 
@@ -81,7 +325,7 @@ pub struct BarSegments {
 }
 
 impl BarSegments {
-    fn new<I: Iterator<Item = S>, S: Into<String>>(blocks: u64, spacing: u64, iter: I) -> Self {
+    pub fn new<I: Iterator<Item = S>, S: Into<String>>(blocks: u64, spacing: u64, iter: I) -> Self {
         let cat_names = iter.map(|s| s.into()).collect();
         Self {
             blocks,
@@ -96,7 +340,7 @@ impl BarSegments {
 impl ValueFormatter<BarSegment> for BarSegments {
     fn format(value: &BarSegment) -> String {
         match value {
-            BarSegment::Normal { cat, .. } => cat.to_string(),
+            BarSegment::Normal { cat, .. } | BarSegment::Inner { cat, .. } => cat.to_string(),
             BarSegment::End => "".to_owned(),
             BarSegment::Center { cat } => cat.to_string(),
         }
@@ -104,7 +348,9 @@ impl ValueFormatter<BarSegment> for BarSegments {
 
     fn format_ext(&self, value: &BarSegment) -> String {
         match value {
-            BarSegment::Normal { cat, .. } => self.cat_names[*cat as usize].clone(),
+            BarSegment::Normal { cat, .. } | BarSegment::Inner { cat, .. } => {
+                self.cat_names[*cat as usize].clone()
+            }
             BarSegment::End => "".to_owned(),
             BarSegment::Center { cat } => self.cat_names[*cat as usize].clone(),
         }
@@ -117,6 +363,8 @@ impl Ranged for BarSegments {
     type ValueType = BarSegment;
 
     fn map(&self, value: &Self::ValueType, limit: (i32, i32)) -> i32 {
+        // always lay out from the lowest pixel, so on the y axis the first category is at the top
+        let limit = (limit.0.min(limit.1), limit.0.max(limit.1));
         let range = (limit.1 - limit.0) as f64;
         let spacing = self.spacing as f64;
         let blocks = (self.cats() * self.blocks) as f64;
@@ -127,6 +375,10 @@ impl Ranged for BarSegments {
                 let x = spacing + *cat as f64 * block_gap + block_w * (*num as f64);
                 limit.0 + x as i32
             }
+            BarSegment::Inner { cat, num, frac } => {
+                let x = spacing + *cat as f64 * block_gap + block_w * (*num as f64 + frac);
+                limit.0 + x as i32
+            }
             BarSegment::End => limit.1,
             BarSegment::Center { cat } => {
                 let x = block_gap * *cat as f64 + block_gap * 0.5;
@@ -154,84 +406,440 @@ impl Ranged for BarSegments {
     }
 }
 
+/// Categories are placed by name or index, at their centre
+impl AnnotationAxis for BarSegments {
+    fn position(&self, v: &AxisValue) -> Option<BarSegment> {
+        let cat = match v {
+            AxisValue::Text(s) => self.cat_names.iter().position(|c| c == s)?,
+            AxisValue::Number(n) if n.fract() == 0.0 && *n >= 0.0 => *n as usize,
+            AxisValue::Number(_) => return None,
+        };
+        (cat < self.cat_names.len()).then_some(BarSegment::Center { cat: cat as u64 })
+    }
+}
+
+/// Value on either axis of a bar chart
+pub enum BarValue {
+    Segment(BarSegment),
+    Value(f64),
+}
+
+/// Axis of a bar chart, which one is which depends on the orientation
+pub enum BarAxis {
+    Categories(BarSegments),
+    Values(ScaledAxis),
+}
+
+impl ValueFormatter<BarValue> for BarAxis {
+    fn format_ext(&self, value: &BarValue) -> String {
+        match (self, value) {
+            (BarAxis::Categories(s), BarValue::Segment(v)) => s.format_ext(v),
+            (BarAxis::Values(r), BarValue::Value(v)) => r.format_ext(v),
+            _ => "".to_owned(),
+        }
+    }
+}
+
+impl Ranged for BarAxis {
+    type FormatOption = NoDefaultFormatting;
+
+    type ValueType = BarValue;
+
+    fn map(&self, value: &Self::ValueType, limit: (i32, i32)) -> i32 {
+        match (self, value) {
+            (BarAxis::Categories(s), BarValue::Segment(v)) => s.map(v, limit),
+            (BarAxis::Values(r), BarValue::Value(v)) => r.map(v, limit),
+            _ => unreachable!("bar value used on the wrong axis"),
+        }
+    }
+
+    fn key_points<Hint: plotters::coord::ranged1d::KeyPointHint>(
+        &self,
+        hint: Hint,
+    ) -> Vec<Self::ValueType> {
+        match self {
+            BarAxis::Categories(s) => s
+                .key_points(hint)
+                .into_iter()
+                .map(BarValue::Segment)
+                .collect(),
+            BarAxis::Values(r) => r
+                .key_points(hint)
+                .into_iter()
+                .map(BarValue::Value)
+                .collect(),
+        }
+    }
+
+    fn range(&self) -> std::ops::Range<Self::ValueType> {
+        match self {
+            BarAxis::Categories(s) => {
+                let r = s.range();
+                BarValue::Segment(r.start)..BarValue::Segment(r.end)
+            }
+            BarAxis::Values(r) => {
+                let r = r.range();
+                BarValue::Value(r.start)..BarValue::Value(r.end)
+            }
+        }
+    }
+}
+
+impl AnnotationAxis for BarAxis {
+    fn position(&self, v: &AxisValue) -> Option<BarValue> {
+        match self {
+            BarAxis::Categories(s) => s.position(v).map(BarValue::Segment),
+            BarAxis::Values(r) => r.position(v).map(BarValue::Value),
+        }
+    }
+}
+
 impl ChartType for BarChart {
     type DataPoint = BarPoint;
-    type X = BarSegments;
-    type Y = plotters::coord::types::RangedCoordu64;
+    type DatasetOptions = BarDataset;
+    type X = BarAxis;
+    type Y = BarAxis;
 
     fn render_datasets<'a, 'b, DB: DrawingBackend>(
         &self,
-        info: &ChartInfo<f64>,
+        info: &ChartInfo<BarPoint, BarDataset>,
         c: &mut ChartBuilder<'a, 'b, DB>,
     ) -> Result<ChartContext<'a, DB, Cartesian2d<Self::X, Self::Y>>> {
         let fiinfo = info.font();
-        let tfont: TextStyle = fiinfo.into_text_style();
-        let max_val = max_val(&info.datasets);
-        let nb_blocks = info.datasets.len();
-        let mut chart = c
-            .set_left_and_bottom_label_area_size(50)
-            .margin(10)
-            .caption(info.caption(), FontFamily::SansSerif)
-            .build_cartesian_2d(
-                BarSegments::new(
-                    nb_blocks as u64,
-                    self.spacing() as u64,
-                    self.categories.iter(),
-                ),
-                0u64..(max_val) as u64,
-            )?;
+        let tfont: TextStyle = info.theme().text_style(&fiinfo);
+        let (ticks, labels) = (info.tick_font(), info.label_font());
+        let nb_blocks = match self.mode() {
+            BarMode::Grouped => info.datasets.len(),
+            BarMode::Stacked | BarMode::Percent => 1,
+        };
+        let segments = BarAxis::Categories(BarSegments::new(
+            nb_blocks as u64,
+            self.spacing() as u64,
+            self.categories.iter(),
+        ));
+        for dset in &info.datasets {
+            for (ncat, v) in dset.values.iter().enumerate() {
+                self.y_scale().check(v.value(), || {
+                    format!("value {} in dataset '{}'", ncat, dset.extra.name)
+                })?;
+            }
+        }
+        let extents = self.extents(&info.datasets);
+        let errors = self.error_extents(&info.datasets, &extents)?;
+        for (low, _) in errors.iter().flatten().flatten() {
+            self.y_scale()
+                .check(*low, || "bottom of an error bar".to_owned())?;
+        }
+        let range = self.value_range(&extents);
+        let range = span(
+            [range.start, range.end].into_iter().chain(
+                errors
+                    .iter()
+                    .flatten()
+                    .flatten()
+                    .flat_map(|(l, h)| [*l, *h]),
+            ),
+        );
+        let values = self.y_axis().scaled(self.y_scale(), range, 0.0)?;
+        // bars that go below the bottom of the axis are cut off
+        let base = values.range().start;
+        let values = BarAxis::Values(values);
+        let value_label = self.y_label.to_owned().unwrap_or("".to_owned());
+        c.set_left_and_bottom_label_area_size(50).margin(10);
+        let mut chart = match self.orientation() {
+            Orientation::Vertical => c.build_cartesian_2d(segments, values)?,
+            Orientation::Horizontal => {
+                let cat_width = self
+                    .categories
+                    .iter()
+                    .map(|cat| ticks.to_text_style().font.box_size(cat).map(|(w, _)| w))
+                    .collect::<std::result::Result<Vec<_>, _>>()
+                    .map_err(|e| render::Error::FontLoading(e.to_string()))?
+                    .into_iter()
+                    .max()
+                    .unwrap_or(0);
+                c.set_label_area_size(LabelAreaPosition::Left, cat_width + 15)
+                    .build_cartesian_2d(values, segments)?
+            }
+        };
         let mut mesh = chart.configure_mesh();
-        mesh.disable_x_mesh()
-            .bold_line_style(&WHITE.mix(0.3))
-            .y_desc(self.y_label.to_owned().unwrap_or("".to_owned()))
-            .y_label_style(tfont.clone());
-        if !self.lines() {
-            mesh.disable_y_mesh();
+        info.theme().style_mesh(&mut mesh, &ticks, &labels);
+        match self.orientation() {
+            Orientation::Vertical => {
+                mesh.disable_x_mesh().y_desc(value_label);
+                if !self.lines() {
+                    mesh.disable_y_mesh();
+                }
+            }
+            Orientation::Horizontal => {
+                mesh.disable_y_mesh().x_desc(value_label);
+                if !self.lines() {
+                    mesh.disable_x_mesh();
+                }
+            }
         }
         mesh.draw()?;
-        let mut citer = colours();
-        for (nset, dset) in info.datasets.iter().enumerate() {
-            let colour = dset
-                .extra
-                .colour
-                .map(|c| c.as_rgba())
-                .unwrap_or_else(|| citer.next().unwrap().to_rgba());
-            chart
-                .draw_series((0..self.categories.len()).map(|ncat| {
-                    Rectangle::new(
-                        [
-                            (
-                                BarSegment::Normal {
-                                    cat: ncat as u64,
-                                    num: nset as u64,
-                                },
-                                0u64,
-                            ),
-                            (
-                                BarSegment::Normal {
-                                    cat: ncat as u64,
-                                    num: nset as u64 + 1,
-                                },
-                                dset.values[ncat] as u64,
-                            ),
-                        ],
-                        colour.filled(),
+        let colours = info.dataset_colours();
+        for (nset, colour) in colours.iter().copied().enumerate() {
+            let num = match self.mode() {
+                BarMode::Grouped => nset as u64,
+                BarMode::Stacked | BarMode::Percent => 0,
+            };
+            chart.draw_series(extents[nset].iter().enumerate().map(|(ncat, (low, high))| {
+                Rectangle::new(
+                    [
+                        self.coord(
+                            BarSegment::Normal {
+                                cat: ncat as u64,
+                                num,
+                            },
+                            low.max(base),
+                        ),
+                        self.coord(
+                            BarSegment::Normal {
+                                cat: ncat as u64,
+                                num: num + 1,
+                            },
+                            *high,
+                        ),
+                    ],
+                    colour.filled(),
+                )
+            }))?;
+        }
+        // drawn after all the bars so stacked bars don't cover them
+        for (nset, errs) in errors.iter().enumerate() {
+            let num = match self.mode() {
+                BarMode::Grouped => nset as u64,
+                BarMode::Stacked | BarMode::Percent => 0,
+            };
+            chart.draw_series(errs.iter().enumerate().filter_map(|(ncat, err)| {
+                let at = || BarSegment::Inner {
+                    cat: ncat as u64,
+                    num,
+                    frac: 0.5,
+                };
+                err.map(|(low, high)| {
+                    Whisker::new(
+                        self.coord(at(), low),
+                        self.coord(at(), high),
+                        ERROR_CAP_WIDTH,
+                        info.theme().text().stroke_width(1),
                     )
-                }))?
-                .label(dset.extra.name.clone())
-                .legend(move |pt| legend_for(pt, colour));
+                })
+            }))?;
+        }
+        let area = chart.plotting_area().strip_coord_spec();
+        let default_position = match self.mode() {
+            BarMode::Grouped => LabelPosition::Outside,
+            BarMode::Stacked | BarMode::Percent => LabelPosition::Inside,
+        };
+        for (nset, dset) in info.datasets.iter().enumerate() {
+            let Some(labels) = self.labels(&dset.options) else {
+                continue;
+            };
+            let num = match self.mode() {
+                BarMode::Grouped => nset as u64,
+                BarMode::Stacked | BarMode::Percent => 0,
+            };
+            for (ncat, (v, (low, high))) in dset.values.iter().zip(&extents[nset]).enumerate() {
+                let v = v.value();
+                let end = if v < 0.0 { *low } else { *high };
+                let at = chart.backend_coord(&self.coord(
+                    BarSegment::Inner {
+                        cat: ncat as u64,
+                        num,
+                        frac: 0.5,
+                    },
+                    end,
+                ));
+                labels.draw(
+                    &area,
+                    at,
+                    self.direction(v),
+                    v,
+                    &tfont,
+                    default_position,
+                    Some(colours[nset]),
+                )?;
+            }
         }
         Ok(chart)
     }
+
+    fn legend_entries<'a, DB: DrawingBackend + 'a>(
+        &self,
+        info: &ChartInfo<Self::DataPoint, Self::DatasetOptions>,
+    ) -> Vec<LegendEntry<'a, DB>> {
+        let mut entries: Vec<_> = info
+            .datasets
+            .iter()
+            .zip(info.dataset_colours())
+            .map(|(dset, colour)| {
+                LegendEntry::new(dset.extra.name.clone(), move |pt| legend_for(pt, colour))
+            })
+            .collect();
+        if self.mode() != BarMode::Grouped {
+            // same order as the stack
+            entries.reverse();
+        }
+        entries
+    }
 }
 
-fn max_val(datasets: &Vec<Dataset<f64>>) -> f64 {
-    datasets
-        .iter()
-        .flat_map(|dset| dset.values.iter().map(|v| v.ceil() as u64))
-        .max()
-        .unwrap() as f64
+/// Sum of the size of every dataset's value for each category
+fn category_totals(datasets: &[Dataset<BarPoint, BarDataset>], ncats: usize) -> Vec<f64> {
+    (0..ncats)
+        .map(|ncat| {
+            datasets
+                .iter()
+                .map(|dset| dset.values.get(ncat).map_or(0.0, BarPoint::value).abs())
+                .sum()
+        })
+        .collect()
 }
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use super::*;
+    use crate::chart::DatasetMeta;
+
+    fn dataset(values: Vec<f64>) -> Dataset<BarPoint, BarDataset> {
+        Dataset {
+            values: values.into_iter().map(BarPoint::Value).collect(),
+            extra: DatasetMeta {
+                name: "".to_owned(),
+                colour: None,
+            },
+            options: BarDataset::default(),
+        }
+    }
+
+    fn chart(mode: BarMode) -> BarChart {
+        BarChart {
+            categories: vec!["a".to_owned(), "b".to_owned()],
+            mode: Some(mode),
+            ..BarChart::new()
+        }
+    }
+
+    #[test]
+    fn test_stacked_extents() {
+        let dsets = vec![dataset(vec![1.0, 2.0]), dataset(vec![3.0, 4.0])];
+        let c = chart(BarMode::Stacked);
+        assert_eq!(
+            c.extents(&dsets),
+            vec![vec![(0.0, 1.0), (0.0, 2.0)], vec![(1.0, 4.0), (2.0, 6.0)]]
+        );
+        assert_eq!(c.value_range(&c.extents(&dsets)), 0.0..6.0);
+    }
+
+    #[test]
+    fn test_percent_extents() {
+        let dsets = vec![dataset(vec![1.0, 0.0]), dataset(vec![3.0, 0.0])];
+        let c = chart(BarMode::Percent);
+        assert_eq!(
+            c.extents(&dsets),
+            vec![
+                vec![(0.0, 25.0), (0.0, 0.0)],
+                vec![(25.0, 100.0), (0.0, 0.0)]
+            ]
+        );
+        assert_eq!(c.value_range(&c.extents(&dsets)), 0.0..100.0);
+    }
+
+    #[test]
+    fn test_negative_extents() {
+        let dsets = vec![dataset(vec![1.0, -2.0]), dataset(vec![3.0, -1.0])];
+        let c = chart(BarMode::Stacked);
+        assert_eq!(
+            c.extents(&dsets),
+            vec![
+                vec![(0.0, 1.0), (-2.0, 0.0)],
+                vec![(1.0, 4.0), (-3.0, -2.0)]
+            ]
+        );
+        assert_eq!(c.value_range(&c.extents(&dsets)), -3.0..4.0);
+
+        let dsets = vec![dataset(vec![1.0, -1.0]), dataset(vec![3.0, -3.0])];
+        let c = chart(BarMode::Percent);
+        assert_eq!(c.extents(&dsets)[1], vec![(25.0, 100.0), (-100.0, -25.0)]);
+        assert_eq!(c.value_range(&c.extents(&dsets)), -100.0..100.0);
+    }
+
+    #[test]
+    fn test_fractional_extents() {
+        let dsets = vec![dataset(vec![0.25, -0.5])];
+        let c = chart(BarMode::Grouped);
+        assert_eq!(c.extents(&dsets), vec![vec![(0.0, 0.25), (-0.5, 0.0)]]);
+        assert_eq!(c.value_range(&c.extents(&dsets)), -0.5..0.25);
+    }
+
+    #[test]
+    fn test_error_extents() {
+        let mut dsets = vec![dataset(vec![1.0, -2.0]), dataset(vec![3.0, 0.0])];
+        dsets[0].values =
+            serde_yaml::from_str("[{value: 1, err: 0.5}, {value: -2, err_low: 1}]").unwrap();
+        dsets[1].values[0] = serde_yaml::from_str("{value: 3, err_high: 1}").unwrap();
+        let c = chart(BarMode::Stacked);
+        let extents = c.extents(&dsets);
+        assert_eq!(
+            c.error_extents(&dsets, &extents).unwrap(),
+            vec![
+                vec![Some((0.5, 1.5)), Some((-3.0, -2.0))],
+                vec![Some((4.0, 5.0)), None]
+            ]
+        );
+        let c = chart(BarMode::Percent);
+        let extents = c.extents(&dsets);
+        assert_eq!(
+            c.error_extents(&dsets, &extents).unwrap()[1][0],
+            Some((100.0, 125.0))
+        );
+    }
+
+    #[test]
+    fn test_category_position() {
+        let segs = BarSegments::new(2, 5, ["a", "b"].into_iter());
+        let cat = |v: AxisValue| match segs.position(&v) {
+            Some(BarSegment::Center { cat }) => Some(cat),
+            _ => None,
+        };
+        assert_eq!(cat(AxisValue::Text("b".to_owned())), Some(1));
+        assert_eq!(cat(AxisValue::Number(0.0)), Some(0));
+        assert_eq!(cat(AxisValue::Number(0.5)), None);
+        assert_eq!(cat(AxisValue::Number(2.0)), None);
+        assert_eq!(cat(AxisValue::Text("c".to_owned())), None);
+    }
+
+    #[test]
+    fn test_dataset_labels() {
+        let c = BarChart {
+            labels: Some(serde_yaml::from_str("format: {suffix: '%'}").unwrap()),
+            ..chart(BarMode::Grouped)
+        };
+        let text = |opts: &str| {
+            let opts: BarDataset = serde_yaml::from_str(opts).unwrap();
+            c.labels(&opts).map(|l| l.text(2.0))
+        };
+        assert_eq!(text("{}"), Some("2%".to_owned()));
+        assert_eq!(text("labels: {}"), Some("2".to_owned()));
+        assert_eq!(text("labels: {show: false}"), None);
+        assert_eq!(
+            chart(BarMode::Grouped)
+                .labels(&BarDataset::default())
+                .map(|_| ()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_all_zero_extents() {
+        let dsets = vec![dataset(vec![0.0, 0.0])];
+        for mode in [BarMode::Grouped, BarMode::Stacked] {
+            let c = chart(mode);
+            assert_eq!(c.extents(&dsets), vec![vec![(0.0, 0.0), (0.0, 0.0)]]);
+            assert_eq!(c.value_range(&c.extents(&dsets)), 0.0..1.0);
+        }
+    }
+}
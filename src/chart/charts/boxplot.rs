@@ -0,0 +1,284 @@
+use plotters::{
+    prelude::{
+        Cartesian2d, ChartBuilder, ChartContext, Circle, DrawingBackend, PathElement, Rectangle,
+    },
+    style::Color,
+};
+use serde::Deserialize;
+
+use super::{
+    bar::{BarSegment, BarSegments},
+    legend_for, Result,
+};
+use crate::{
+    chart::{
+        axis::AxisOptions,
+        legend::LegendEntry,
+        scale::{span, Scale, ScaledAxis},
+        ChartInfo, ChartType,
+    },
+    render,
+    utils::quantile,
+};
+
+/// Samples for each category
+pub type Samples = Vec<f64>;
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Whiskers {
+    /// Furthest samples within `iqr_factor * IQR` of the box, anything past that is an outlier
+    #[default]
+    Iqr,
+    /// Smallest and largest samples, there are no outliers
+    MinMax,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct BoxPlot {
+    /// Categories of boxes, appear along the x axis
+    categories: Vec<String>,
+    /// Spacing between box groups
+    spacing: Option<f64>,
+    /// Where the whiskers end, default: iqr
+    whiskers: Option<Whiskers>,
+    /// Multiple of the IQR the whiskers can reach, default: 1.5
+    iqr_factor: Option<f64>,
+    /// Label for the y axis
+    y_label: Option<String>,
+    /// Range and ticks of the y axis, padding defaults to 5
+    y_axis: Option<AxisOptions>,
+}
+
+impl BoxPlot {
+    fn spacing(&self) -> f64 {
+        self.spacing.to_owned().unwrap_or(5.0)
+    }
+
+    fn whiskers(&self) -> Whiskers {
+        self.whiskers.unwrap_or_default()
+    }
+
+    fn iqr_factor(&self) -> Result<f64> {
+        match self.iqr_factor.unwrap_or(1.5) {
+            f if f >= 0.0 && f.is_finite() => Ok(f),
+            f => Err(render::Error::InvalidChart(format!(
+                "box plot iqr_factor must be 0 or more, got {}",
+                f
+            ))),
+        }
+    }
+
+    fn y_axis(&self) -> AxisOptions {
+        self.y_axis.clone().unwrap_or_default()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct BoxStats {
+    low: f64,
+    q1: f64,
+    median: f64,
+    q3: f64,
+    high: f64,
+    outliers: Vec<f64>,
+}
+
+impl BoxStats {
+    fn new(samples: &[f64], whiskers: Whiskers, iqr_factor: f64) -> Option<Self> {
+        let mut sorted: Vec<_> = samples.iter().copied().filter(|v| v.is_finite()).collect();
+        if sorted.is_empty() {
+            return None;
+        }
+        sorted.sort_by(f64::total_cmp);
+        let q1 = quantile(&sorted, 0.25);
+        let median = quantile(&sorted, 0.5);
+        let q3 = quantile(&sorted, 0.75);
+        let (low, high, outliers) = match whiskers {
+            Whiskers::MinMax => (sorted[0], sorted[sorted.len() - 1], Vec::new()),
+            Whiskers::Iqr => {
+                let reach = (q3 - q1) * iqr_factor;
+                let (inside, outliers): (Vec<f64>, Vec<f64>) = sorted
+                    .iter()
+                    .partition(|v| **v >= q1 - reach && **v <= q3 + reach);
+                // with a small factor no samples may be inside, then the whiskers are left at the box
+                (
+                    inside.first().map_or(q1, |v| v.min(q1)),
+                    inside.last().map_or(q3, |v| v.max(q3)),
+                    outliers,
+                )
+            }
+        };
+        Some(Self {
+            low,
+            q1,
+            median,
+            q3,
+            high,
+            outliers,
+        })
+    }
+
+    /// Smallest and largest value drawn
+    fn extent(&self) -> (f64, f64) {
+        self.outliers
+            .iter()
+            .fold((self.low, self.high), |(l, h), v| (l.min(*v), h.max(*v)))
+    }
+}
+
+impl ChartType for BoxPlot {
+    type DataPoint = Samples;
+    type DatasetOptions = ();
+    type X = BarSegments;
+    type Y = ScaledAxis;
+
+    fn render_datasets<'a, 'b, DB: DrawingBackend>(
+        &self,
+        info: &ChartInfo<Self::DataPoint>,
+        c: &mut ChartBuilder<'a, 'b, DB>,
+    ) -> Result<ChartContext<'a, DB, Cartesian2d<Self::X, Self::Y>>> {
+        let (ticks, labels) = (info.tick_font(), info.label_font());
+        let iqr_factor = self.iqr_factor()?;
+
+        let stats: Vec<Vec<_>> = info
+            .datasets
+            .iter()
+            .map(|dset| {
+                dset.values
+                    .iter()
+                    .take(self.categories.len())
+                    .map(|s| BoxStats::new(s, self.whiskers(), iqr_factor))
+                    .collect()
+            })
+            .collect();
+        let range = span(
+            stats
+                .iter()
+                .flatten()
+                .flatten()
+                .flat_map(|s| <[f64; 2]>::from(s.extent())),
+        );
+
+        let mut chart = c
+            .set_left_and_bottom_label_area_size(50)
+            .margin(10)
+            .build_cartesian_2d(
+                BarSegments::new(
+                    info.datasets.len() as u64,
+                    self.spacing() as u64,
+                    self.categories.iter(),
+                ),
+                self.y_axis().scaled(Scale::Linear, range, 5.0)?,
+            )?;
+        let mut mesh = chart.configure_mesh();
+        info.theme().style_mesh(&mut mesh, &ticks, &labels);
+        mesh.disable_x_mesh()
+            .y_desc(self.y_label.to_owned().unwrap_or_default())
+            .draw()?;
+
+        // Drawn from rectangles and paths rather than plotters' `Boxplot`, which takes its
+        // values from `Quartiles::new`: that always puts the whiskers at 1.5 IQR from the box,
+        // rather than at the furthest sample inside the reach or at `iqr_factor`, and has no
+        // min-max option. `Boxplot` also needs f32 y coordinates, so it can't go on a
+        // `ScaledAxis`, and its width is in pixels instead of a share of the category
+        let colours = info.dataset_colours();
+        for (nset, (stats, colour)) in stats.into_iter().zip(colours).enumerate() {
+            let stats: Vec<_> = stats
+                .into_iter()
+                .enumerate()
+                .filter_map(|(ncat, s)| Some((ncat as u64, s?)))
+                .collect();
+            let at = move |cat: u64, frac: f64, y: f64| {
+                (
+                    BarSegment::Inner {
+                        cat,
+                        num: nset as u64,
+                        frac,
+                    },
+                    y,
+                )
+            };
+            chart.draw_series(stats.iter().map(|(cat, s)| {
+                Rectangle::new(
+                    [at(*cat, 0.15, s.q1), at(*cat, 0.85, s.q3)],
+                    colour.mix(0.3).filled(),
+                )
+            }))?;
+            chart.draw_series(stats.iter().map(|(cat, s)| {
+                Rectangle::new(
+                    [at(*cat, 0.15, s.q1), at(*cat, 0.85, s.q3)],
+                    colour.stroke_width(1),
+                )
+            }))?;
+            chart.draw_series(stats.iter().flat_map(|(cat, s)| {
+                [
+                    vec![at(*cat, 0.15, s.median), at(*cat, 0.85, s.median)],
+                    vec![at(*cat, 0.5, s.q3), at(*cat, 0.5, s.high)],
+                    vec![at(*cat, 0.5, s.q1), at(*cat, 0.5, s.low)],
+                    vec![at(*cat, 0.3, s.high), at(*cat, 0.7, s.high)],
+                    vec![at(*cat, 0.3, s.low), at(*cat, 0.7, s.low)],
+                ]
+                .into_iter()
+                .map(|pts| PathElement::new(pts, colour.stroke_width(1)))
+            }))?;
+            chart.draw_series(stats.iter().flat_map(|(cat, s)| {
+                s.outliers
+                    .iter()
+                    .map(|v| Circle::new(at(*cat, 0.5, *v), 3, colour.stroke_width(1)))
+            }))?;
+        }
+        Ok(chart)
+    }
+
+    fn legend_entries<'a, DB: DrawingBackend + 'a>(
+        &self,
+        info: &ChartInfo<Self::DataPoint, Self::DatasetOptions>,
+    ) -> Vec<LegendEntry<'a, DB>> {
+        info.datasets
+            .iter()
+            .zip(info.dataset_colours())
+            .map(|(dset, colour)| {
+                LegendEntry::new(dset.extra.name.clone(), move |pt| legend_for(pt, colour))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_box_stats_iqr() {
+        let samples = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 100.0];
+        let s = BoxStats::new(&samples, Whiskers::Iqr, 1.5).unwrap();
+        assert_eq!((s.q1, s.median, s.q3), (3.25, 5.5, 7.75));
+        assert_eq!((s.low, s.high), (1.0, 9.0));
+        assert_eq!(s.outliers, vec![100.0]);
+        assert_eq!(s.extent(), (1.0, 100.0));
+    }
+
+    #[test]
+    fn test_box_stats_min_max() {
+        let samples = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 100.0];
+        let s = BoxStats::new(&samples, Whiskers::MinMax, 1.5).unwrap();
+        assert_eq!((s.low, s.high), (1.0, 100.0));
+        assert!(s.outliers.is_empty());
+        assert!(BoxStats::new(&[], Whiskers::MinMax, 1.5).is_none());
+    }
+
+    #[test]
+    fn test_box_stats_nothing_inside() {
+        let s = BoxStats::new(&[1.0, 10.0], Whiskers::Iqr, 0.0).unwrap();
+        assert_eq!((s.low, s.q1, s.q3, s.high), (s.q1, 3.25, 7.75, s.q3));
+        assert_eq!(s.outliers, vec![1.0, 10.0]);
+        let chart = |f: &str| {
+            serde_yaml::from_str::<BoxPlot>(&format!("{{categories: [c], iqr_factor: {}}}", f))
+                .unwrap()
+        };
+        assert!(chart("-1").iqr_factor().is_err());
+        assert!(chart(".nan").iqr_factor().is_err());
+        assert_eq!(chart("0").iqr_factor().unwrap(), 0.0);
+    }
+}
@@ -0,0 +1,310 @@
+use plotters::coord::ranged1d::{KeyPointHint, NoDefaultFormatting, ValueFormatter};
+use plotters::coord::Shift;
+use plotters::prelude::{ChartBuilder, DrawingArea, DrawingBackend, Ranged, Rectangle, Text};
+use plotters::style::text_anchor::{HPos, Pos, VPos};
+use plotters::style::Color;
+use serde::Deserialize;
+
+use super::Result;
+use crate::chart::annotation::{AnnotationAxis, AxisValue};
+use crate::chart::axis::{AxisOptions, Ticks};
+use crate::chart::scale::{span, Scale};
+use crate::chart::Chart;
+use crate::palette::{contrasting, ColourScale};
+use crate::render::{self, Render};
+
+/// Value of a single cell, each dataset is a row
+pub type Cell = f64;
+
+const COLOUR_BAR_WIDTH: i32 = 15;
+const COLOUR_BAR_GAP: i32 = 10;
+const COLOUR_BAR_STEPS: i32 = 64;
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Heatmap {
+    /// Names of the columns, appear along the x axis. Rows are named by their dataset
+    columns: Vec<String>,
+    /// Colour scale for the values, either a named scale or a list of css colours. default: viridis
+    #[serde(alias = "color_scale")]
+    colour_scale: Option<ColourScale>,
+    /// Print the value in each cell? default: false
+    labels: Option<bool>,
+    /// Decimal places for cell labels and the colour bar, default: 1
+    precision: Option<usize>,
+    /// Draw a colour bar showing the scale? default: true
+    colour_bar: Option<bool>,
+    /// Values at each end of the colour scale and the ticks on the colour bar, values outside the
+    /// range get the colour at its end
+    #[serde(alias = "color_axis")]
+    colour_axis: Option<AxisOptions>,
+}
+
+impl Heatmap {
+    fn colour_scale(&self) -> ColourScale {
+        self.colour_scale.clone().unwrap_or_default()
+    }
+
+    fn labels(&self) -> bool {
+        self.labels.unwrap_or(false)
+    }
+
+    fn precision(&self) -> usize {
+        self.precision.unwrap_or(1)
+    }
+
+    fn colour_bar(&self) -> bool {
+        self.colour_bar.unwrap_or(true)
+    }
+
+    fn colour_axis(&self) -> AxisOptions {
+        self.colour_axis.clone().unwrap_or_default()
+    }
+
+    /// Values to label on the colour bar for a colour scale covering `range`, a count of ticks
+    /// is limited to `max`
+    fn colour_ticks(&self, range: &std::ops::Range<f64>, max: usize) -> Vec<f64> {
+        let even = |n: usize| {
+            let n = n.max(2) - 1;
+            (0..=n)
+                .map(|i| range.start + (range.end - range.start) * i as f64 / n as f64)
+                .collect()
+        };
+        match self.colour_axis().ticks() {
+            Some(Ticks::At(at)) => at
+                .iter()
+                .copied()
+                .filter(|v| range.contains(v) || *v == range.end)
+                .collect(),
+            Some(Ticks::Count(n)) => even((*n).min(max)),
+            None => even(5),
+        }
+    }
+
+    fn tick_label(&self, v: f64) -> String {
+        let default = format!("{:.*}", self.precision(), v);
+        match self.colour_axis().tick_format() {
+            Some(f) => f.format(v, default),
+            None => default,
+        }
+    }
+}
+
+/// Axis of named cells each one unit wide, labelled at their centres
+pub struct CellAxis {
+    names: Vec<String>,
+    /// Lay out the cells from the end of the axis, for rows that go top to bottom
+    reversed: bool,
+}
+
+impl CellAxis {
+    fn new<I: Iterator<Item = S>, S: Into<String>>(iter: I, reversed: bool) -> Self {
+        Self {
+            names: iter.map(|s| s.into()).collect(),
+            reversed,
+        }
+    }
+
+    fn len(&self) -> f64 {
+        self.names.len() as f64
+    }
+}
+
+impl ValueFormatter<f64> for CellAxis {
+    fn format_ext(&self, value: &f64) -> String {
+        self.names
+            .get(value.floor() as usize)
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+impl Ranged for CellAxis {
+    type FormatOption = NoDefaultFormatting;
+
+    type ValueType = f64;
+
+    fn map(&self, value: &f64, limit: (i32, i32)) -> i32 {
+        let v = if self.reversed {
+            self.len() - value
+        } else {
+            *value
+        };
+        limit.0 + ((limit.1 - limit.0) as f64 * v / self.len()).round() as i32
+    }
+
+    fn key_points<Hint: KeyPointHint>(&self, _hint: Hint) -> Vec<f64> {
+        (0..self.names.len()).map(|n| n as f64 + 0.5).collect()
+    }
+
+    fn range(&self) -> std::ops::Range<f64> {
+        0.0..self.len()
+    }
+}
+
+/// Cells are placed by name at their centre, numbers are in cells from the start of the axis
+impl AnnotationAxis for CellAxis {
+    fn position(&self, v: &AxisValue) -> Option<f64> {
+        match v {
+            AxisValue::Number(v) => Some(*v),
+            AxisValue::Text(s) => self
+                .names
+                .iter()
+                .position(|n| n == s)
+                .map(|n| n as f64 + 0.5),
+        }
+    }
+}
+
+impl Render for Chart<Heatmap, Cell> {
+    type Error = render::Error;
+
+    fn render<DB: DrawingBackend>(&self, root: &DrawingArea<DB, Shift>) -> Result<()> {
+        let rows: Vec<_> = self.info.datasets.iter().collect();
+        let ncols = self.extra.columns.len();
+        if rows.is_empty() || ncols == 0 {
+            return Err(render::Error::InvalidChart(
+                "heatmap needs at least one row and column".to_owned(),
+            ));
+        }
+        let range = self.extra.colour_axis().range(
+            Scale::Linear,
+            span(
+                rows.iter()
+                    .flat_map(|r| r.values.iter().take(ncols))
+                    .copied()
+                    .filter(|v| v.is_finite()),
+            ),
+            0.0,
+        )?;
+        let (min, span) = (range.start, range.end - range.start);
+        let (tick_font, label_font) = (self.info.tick_font(), self.info.label_font());
+        // more labels than fit down the bar would only overlap
+        let max_ticks = (root.dim_in_pixel().1 as f64 / tick_font.size.max(1.0)) as usize;
+        let ticks = self.extra.colour_ticks(&range, max_ticks);
+        let scale = self.extra.colour_scale();
+        let precision = self.extra.precision();
+
+        let fiinfo = self.info.font();
+        let tfont = self.info.theme().text_style(&fiinfo);
+        let tick_style = self.info.theme().text_style(&tick_font);
+        let bar_space = if self.extra.colour_bar() {
+            let mut label_w = 0;
+            for v in &ticks {
+                let (w, _) = tick_style
+                    .font
+                    .box_size(&self.extra.tick_label(*v))
+                    .map_err(|e| render::Error::FontLoading(e.to_string()))?;
+                label_w = label_w.max(w as i32);
+            }
+            COLOUR_BAR_GAP * 2 + COLOUR_BAR_WIDTH + label_w
+        } else {
+            0
+        };
+        let (plot_area, bar_area) =
+            root.split_horizontally(root.dim_in_pixel().0 as i32 - bar_space);
+        let caption_font = self.info.caption_font();
+        let mut c = ChartBuilder::on(&plot_area);
+        self.info.apply_margins(&mut c);
+        self.info.apply_caption(&mut c, &caption_font);
+        c.set_left_and_bottom_label_area_size(50).margin(10);
+        let mut chart = c.build_cartesian_2d(
+            CellAxis::new(self.extra.columns.iter(), false),
+            CellAxis::new(rows.iter().map(|r| &r.extra.name), true),
+        )?;
+        let mut mesh = chart.configure_mesh();
+        self.info
+            .theme()
+            .style_mesh(&mut mesh, &tick_font, &label_font);
+        mesh.disable_mesh().draw()?;
+
+        let cells: Vec<_> = rows
+            .iter()
+            .enumerate()
+            .flat_map(|(nrow, r)| {
+                r.values
+                    .iter()
+                    .take(ncols)
+                    .enumerate()
+                    .filter(|(_, v)| v.is_finite())
+                    .map(move |(ncol, v)| (ncol as f64, nrow as f64, *v))
+            })
+            .collect();
+        chart.draw_series(cells.iter().map(|(x, y, v)| {
+            Rectangle::new(
+                [(*x, *y), (x + 1.0, y + 1.0)],
+                scale.at((v - min) / span).filled(),
+            )
+        }))?;
+        if self.extra.labels() {
+            let centred = tfont.pos(Pos::new(HPos::Center, VPos::Center));
+            chart.draw_series(cells.iter().map(|(x, y, v)| {
+                let colour = contrasting(scale.at((v - min) / span));
+                Text::new(
+                    format!("{:.*}", precision, v),
+                    (x + 0.5, y + 0.5),
+                    centred.color(colour),
+                )
+            }))?;
+        }
+        self.info.draw_annotations(&chart)?;
+
+        if self.extra.colour_bar() {
+            // line the bar up with the cells
+            let (_, ys) = chart.plotting_area().get_pixel_range();
+            let base = bar_area.get_base_pixel().1;
+            let (top, h) = (ys.start - base, ys.end - ys.start);
+            let x0 = COLOUR_BAR_GAP;
+            let x1 = x0 + COLOUR_BAR_WIDTH;
+            for step in 0..COLOUR_BAR_STEPS {
+                let y0 = top + h - h * step / COLOUR_BAR_STEPS;
+                let y1 = top + h - h * (step + 1) / COLOUR_BAR_STEPS;
+                let t = (step as f64 + 0.5) / COLOUR_BAR_STEPS as f64;
+                bar_area.draw(&Rectangle::new([(x0, y0), (x1, y1)], scale.at(t).filled()))?;
+            }
+            bar_area.draw(&Rectangle::new(
+                [(x0, top), (x1, top + h)],
+                self.info.theme().axis().stroke_width(1),
+            ))?;
+            let tick_text = tick_style.pos(Pos::new(HPos::Left, VPos::Center));
+            for v in ticks {
+                let y = top + h - ((v - min) / span * h as f64).round() as i32;
+                bar_area.draw(&Text::new(
+                    self.extra.tick_label(v),
+                    (x1 + 5, y),
+                    tick_text.clone(),
+                ))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_colour_ticks() {
+        let heatmap = |yaml: &str| -> Heatmap { serde_yaml::from_str(yaml).unwrap() };
+        let default = heatmap("columns: []");
+        assert_eq!(
+            default.colour_ticks(&(0.0..1.0), 100),
+            vec![0.0, 0.25, 0.5, 0.75, 1.0]
+        );
+        let counted = heatmap("{columns: [], colour_axis: {ticks: 3}}");
+        assert_eq!(
+            counted.colour_ticks(&(-1.0..1.0), 100),
+            vec![-1.0, 0.0, 1.0]
+        );
+        let many = heatmap("{columns: [], colour_axis: {ticks: 300000000}}");
+        assert_eq!(
+            many.colour_ticks(&(0.0..1.0), 5),
+            vec![0.0, 0.25, 0.5, 0.75, 1.0]
+        );
+        let at = heatmap(
+            "{columns: [], color_axis: {ticks: [-1, 0, 0.5, 2], tick_format: {suffix: '%'}}}",
+        );
+        assert_eq!(at.colour_ticks(&(0.0..1.0), 1), vec![0.0, 0.5]);
+        assert_eq!(at.tick_label(0.5), "0.5%");
+    }
+}
@@ -0,0 +1,285 @@
+use plotters::{
+    prelude::{Cartesian2d, ChartBuilder, ChartContext, DrawingBackend, Rectangle},
+    style::Color,
+};
+use serde::Deserialize;
+
+use super::{legend_for, Result};
+use crate::{
+    chart::{
+        axis::AxisOptions,
+        legend::LegendEntry,
+        scale::{Scale, ScaledAxis},
+        ChartInfo, ChartType, Dataset,
+    },
+    render,
+    utils::quantile,
+};
+
+pub type Sample = f64;
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum BinRule {
+    /// `log2(n) + 1` bins
+    Sturges,
+    /// Bins of width `2 * IQR / cbrt(n)`
+    FreedmanDiaconis,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum Bins {
+    /// Fixed number of bins
+    Count(usize),
+    /// Fixed width of each bin
+    Width {
+        width: f64,
+    },
+    Rule(BinRule),
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum HistogramLayout {
+    /// Datasets drawn over each other
+    #[default]
+    Overlap,
+    /// Datasets drawn next to each other within a bin
+    SideBySide,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Histogram {
+    /// How to bin the samples, default: sturges
+    bins: Option<Bins>,
+    /// How datasets share a bin, default: overlap
+    layout: Option<HistogramLayout>,
+    /// Opacity of the bars, default: 0.5 if overlapping otherwise 1
+    opacity: Option<f64>,
+    /// Label for the x axis
+    x_label: Option<String>,
+    /// Label for the y axis
+    y_label: Option<String>,
+    /// Range and ticks of the x axis
+    x_axis: Option<AxisOptions>,
+    /// Range and ticks of the y axis
+    y_axis: Option<AxisOptions>,
+}
+
+impl Histogram {
+    fn bins(&self) -> Bins {
+        self.bins.unwrap_or(Bins::Rule(BinRule::Sturges))
+    }
+
+    fn layout(&self) -> HistogramLayout {
+        self.layout.unwrap_or_default()
+    }
+
+    fn x_axis(&self) -> AxisOptions {
+        self.x_axis.clone().unwrap_or_default()
+    }
+
+    fn y_axis(&self) -> AxisOptions {
+        self.y_axis.clone().unwrap_or_default()
+    }
+
+    fn opacity(&self) -> f64 {
+        self.opacity.unwrap_or(match self.layout() {
+            HistogramLayout::Overlap => 0.5,
+            HistogramLayout::SideBySide => 1.0,
+        })
+    }
+}
+
+/// More bins than this couldn't be told apart, and would take a lot of memory
+const MAX_BINS: usize = 10_000;
+
+/// Edges of each bin, one more than the number of bins
+fn bin_edges(samples: &[f64], bins: Bins) -> Result<Vec<f64>> {
+    let mut sorted: Vec<_> = samples.iter().copied().filter(|v| v.is_finite()).collect();
+    sorted.sort_by(f64::total_cmp);
+    let (min, max) = match (sorted.first(), sorted.last()) {
+        (Some(min), Some(max)) => (*min, *max),
+        _ => {
+            return Err(render::Error::InvalidChart(
+                "histogram has no samples".to_owned(),
+            ))
+        }
+    };
+    if min == max {
+        return Ok(vec![min - 0.5, max + 0.5]);
+    }
+    let sturges = || (sorted.len() as f64).log2().ceil() as usize + 1;
+    let count = match bins {
+        Bins::Count(0) => {
+            return Err(render::Error::InvalidChart(
+                "histogram bin count must be positive".to_owned(),
+            ))
+        }
+        Bins::Count(n) => n,
+        Bins::Width { width } if width <= 0.0 || !width.is_finite() => {
+            return Err(render::Error::InvalidChart(format!(
+                "histogram bin width must be positive, got {}",
+                width
+            )))
+        }
+        Bins::Width { width } => ((max - min) / width).ceil().max(1.0) as usize,
+        Bins::Rule(BinRule::Sturges) => sturges(),
+        Bins::Rule(BinRule::FreedmanDiaconis) => {
+            let iqr = quantile(&sorted, 0.75) - quantile(&sorted, 0.25);
+            let width = 2.0 * iqr / (sorted.len() as f64).cbrt();
+            if width > 0.0 {
+                ((max - min) / width).ceil().min(MAX_BINS as f64) as usize
+            } else {
+                sturges()
+            }
+        }
+    };
+    if count > MAX_BINS {
+        return Err(render::Error::InvalidChart(format!(
+            "histogram has {} bins, it can have at most {}",
+            count, MAX_BINS
+        )));
+    }
+    let width = match bins {
+        Bins::Width { width } => width,
+        _ => (max - min) / count as f64,
+    };
+    Ok((0..=count).map(|i| min + width * i as f64).collect())
+}
+
+/// Number of samples falling in each bin, the last bin includes its upper edge
+fn bin_counts(samples: &[f64], edges: &[f64]) -> Vec<u64> {
+    let nbins = edges.len() - 1;
+    let mut counts = vec![0; nbins];
+    for v in samples.iter().filter(|v| v.is_finite()) {
+        let n = edges[1..].partition_point(|e| e <= v).min(nbins - 1);
+        counts[n] += 1;
+    }
+    counts
+}
+
+impl ChartType for Histogram {
+    type DataPoint = Sample;
+    type DatasetOptions = ();
+    type X = ScaledAxis;
+    type Y = ScaledAxis;
+
+    fn render_datasets<'a, 'b, DB: DrawingBackend>(
+        &self,
+        info: &ChartInfo<Self::DataPoint>,
+        c: &mut ChartBuilder<'a, 'b, DB>,
+    ) -> Result<ChartContext<'a, DB, Cartesian2d<Self::X, Self::Y>>> {
+        let (ticks, labels) = (info.tick_font(), info.label_font());
+
+        let all: Vec<_> = info
+            .datasets
+            .iter()
+            .flat_map(|d: &Dataset<Sample>| d.values.iter().copied())
+            .collect();
+        let edges = bin_edges(&all, self.bins())?;
+        let counts: Vec<_> = info
+            .datasets
+            .iter()
+            .map(|d| bin_counts(&d.values, &edges))
+            .collect();
+        let max_count = counts.iter().flatten().copied().max().unwrap_or(0).max(1);
+
+        let mut chart = c
+            .set_left_and_bottom_label_area_size(50)
+            .margin(10)
+            .build_cartesian_2d(
+                self.x_axis()
+                    .scaled(Scale::Linear, edges[0]..edges[edges.len() - 1], 0.0)?,
+                self.y_axis()
+                    .scaled(Scale::Linear, 0.0..max_count as f64, 0.0)?,
+            )?;
+        let mut mesh = chart.configure_mesh();
+        info.theme().style_mesh(&mut mesh, &ticks, &labels);
+        mesh.disable_x_mesh()
+            .x_desc(self.x_label.to_owned().unwrap_or_default())
+            .y_desc(self.y_label.to_owned().unwrap_or_default())
+            .draw()?;
+
+        let colours = info.dataset_colours();
+        let nsets = colours.len();
+        let opacity = self.opacity();
+        for (nset, (counts, colour)) in counts.into_iter().zip(colours).enumerate() {
+            let colour = colour.mix(opacity);
+            let layout = self.layout();
+            chart.draw_series(edges.windows(2).zip(counts).map(|(edge, count)| {
+                let (x0, x1) = match layout {
+                    HistogramLayout::Overlap => (edge[0], edge[1]),
+                    HistogramLayout::SideBySide => {
+                        let w = (edge[1] - edge[0]) / nsets as f64;
+                        (edge[0] + w * nset as f64, edge[0] + w * (nset + 1) as f64)
+                    }
+                };
+                Rectangle::new([(x0, 0.0), (x1, count as f64)], colour.filled())
+            }))?;
+        }
+        Ok(chart)
+    }
+
+    fn legend_entries<'a, DB: DrawingBackend + 'a>(
+        &self,
+        info: &ChartInfo<Self::DataPoint, Self::DatasetOptions>,
+    ) -> Vec<LegendEntry<'a, DB>> {
+        let opacity = self.opacity();
+        info.datasets
+            .iter()
+            .zip(info.dataset_colours())
+            .map(|(dset, colour)| {
+                LegendEntry::new(dset.extra.name.clone(), move |pt| {
+                    legend_for(pt, colour.mix(opacity))
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bin_edges() {
+        let samples = [0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0];
+        // log2(8) + 1
+        assert_eq!(
+            bin_edges(&samples, Bins::Rule(BinRule::Sturges)).unwrap(),
+            vec![0.0, 1.75, 3.5, 5.25, 7.0]
+        );
+        assert_eq!(
+            bin_edges(&samples, Bins::Width { width: 5.0 }).unwrap(),
+            vec![0.0, 5.0, 10.0]
+        );
+        assert_eq!(
+            bin_edges(&samples, Bins::Count(2)).unwrap(),
+            vec![0.0, 3.5, 7.0]
+        );
+        assert!(bin_edges(&[], Bins::Count(2)).is_err());
+        assert!(bin_edges(&samples, Bins::Count(0)).is_err());
+        assert!(bin_edges(&samples, Bins::Count(MAX_BINS + 1)).is_err());
+        assert!(bin_edges(&samples, Bins::Width { width: 1e-9 }).is_err());
+        // a few far outliers make the automatic width tiny, it is capped rather than an error
+        let mut outliers: Vec<_> = (0..100).map(f64::from).collect();
+        outliers.push(1e12);
+        assert!(
+            bin_edges(&outliers, Bins::Rule(BinRule::FreedmanDiaconis))
+                .unwrap()
+                .len()
+                <= MAX_BINS + 1
+        );
+    }
+
+    #[test]
+    fn test_bin_counts() {
+        let edges = [0.0, 1.0, 2.0, 3.0];
+        assert_eq!(
+            bin_counts(&[0.0, 0.5, 1.0, 2.5, 3.0, f64::NAN], &edges),
+            vec![2, 1, 2]
+        );
+    }
+}
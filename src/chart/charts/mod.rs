@@ -1,23 +1,103 @@
 pub mod bar;
+pub mod boxplot;
+pub mod heatmap;
+pub mod histogram;
+pub mod pie;
 pub mod xyscatter;
 
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
 use plotters::prelude::Rectangle;
 use serde::Deserialize;
 
-use self::{bar::BarPoint, xyscatter::XYScatter};
-use super::{Chart, XY};
+use self::{bar::BarPoint, pie::PiePoint, xyscatter::XYScatter};
+use super::{figure::Figure, Chart, XY};
+use crate::theme::Theme;
 
 #[derive(Clone, Debug, Deserialize)]
 #[serde(tag = "type")]
 pub enum Charts {
     #[serde(rename = "xy-scatter")]
-    XYScatter(Chart<XYScatter, XY<f64>>),
+    XYScatter(Box<Chart<XYScatter, xyscatter::XYPoint, xyscatter::XYDataset>>),
     #[serde(rename = "bar")]
-    Bar(Chart<bar::BarChart, BarPoint>),
+    Bar(Chart<bar::BarChart, BarPoint, bar::BarDataset>),
+    #[serde(rename = "box")]
+    Box(Chart<boxplot::BoxPlot, boxplot::Samples>),
+    #[serde(rename = "heatmap")]
+    Heatmap(Chart<heatmap::Heatmap, heatmap::Cell>),
+    #[serde(rename = "histogram")]
+    Histogram(Chart<histogram::Histogram, histogram::Sample>),
+    #[serde(rename = "pie")]
+    Pie(Chart<pie::PieChart, PiePoint>),
+    /// Grid of other charts
+    #[serde(rename = "figure")]
+    Figure(Box<Figure>),
 }
 
 type Result<T> = std::result::Result<T, crate::render::Error>;
 
+impl Charts {
+    /// See [`Chart::load_theme`]
+    pub fn load_theme(
+        &mut self,
+        theme: Option<&Theme>,
+        parent: &Theme,
+        dir: &Path,
+    ) -> anyhow::Result<&Theme> {
+        match self {
+            Charts::XYScatter(c) => c.load_theme(theme, parent, dir),
+            Charts::Bar(c) => c.load_theme(theme, parent, dir),
+            Charts::Box(c) => c.load_theme(theme, parent, dir),
+            Charts::Heatmap(c) => c.load_theme(theme, parent, dir),
+            Charts::Histogram(c) => c.load_theme(theme, parent, dir),
+            Charts::Pie(c) => c.load_theme(theme, parent, dir),
+            Charts::Figure(f) => f.load_theme(theme, parent, dir),
+        }
+    }
+
+    /// See [`Chart::theme`]
+    pub fn theme(&self) -> &Theme {
+        match self {
+            Charts::XYScatter(c) => c.theme(),
+            Charts::Bar(c) => c.theme(),
+            Charts::Box(c) => c.theme(),
+            Charts::Heatmap(c) => c.theme(),
+            Charts::Histogram(c) => c.theme(),
+            Charts::Pie(c) => c.theme(),
+            Charts::Figure(f) => f.theme(),
+        }
+    }
+
+    /// Font files named anywhere in the spec, relative to it
+    pub fn font_files(&self) -> Vec<PathBuf> {
+        match self {
+            Charts::XYScatter(c) => c.font_files().to_vec(),
+            Charts::Bar(c) => c.font_files().to_vec(),
+            Charts::Box(c) => c.font_files().to_vec(),
+            Charts::Heatmap(c) => c.font_files().to_vec(),
+            Charts::Histogram(c) => c.font_files().to_vec(),
+            Charts::Pie(c) => c.font_files().to_vec(),
+            Charts::Figure(f) => f.font_files(),
+        }
+    }
+
+    /// Ranges of the x and primary y axes, for the charts which can share them in a figure
+    pub fn axis_ranges(&self) -> Option<Result<XY<Range<f64>>>> {
+        match self {
+            Charts::XYScatter(c) => Some(c.axis_ranges()),
+            _ => None,
+        }
+    }
+
+    /// Fix the x and primary y axes to these ranges, for the charts which can share them
+    pub fn set_axis_ranges(&mut self, x: Option<Range<f64>>, y: Option<Range<f64>>) {
+        if let Charts::XYScatter(c) = self {
+            c.set_axis_ranges(x, y);
+        }
+    }
+}
+
 fn legend_for<C: plotters::style::Color>(
     (x, y): (i32, i32),
     c: C,
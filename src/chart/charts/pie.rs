@@ -0,0 +1,223 @@
+use std::f64::consts::PI;
+
+use plotters::coord::Shift;
+use plotters::prelude::{ChartBuilder, DrawingArea, DrawingBackend, Polygon, Text};
+use plotters::style::text_anchor::{HPos, Pos, VPos};
+use plotters::style::{Color, RGBAColor};
+use serde::Deserialize;
+
+use super::{legend_for, Result};
+use crate::chart::legend::LegendEntry;
+use crate::chart::Chart;
+use crate::palette::{contrasting, PaletteIter};
+use crate::render::{self, CssColour, Render};
+
+pub type PiePoint = f64;
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct PieChart {
+    /// Name of each slice, in the same order as the values
+    categories: Vec<String>,
+    /// Colour of each slice, default: taken from the palette
+    #[serde(alias = "colors")]
+    colours: Option<Vec<CssColour>>,
+    /// Radius of the hole in the middle as a fraction of the radius, default: 0
+    inner_radius: Option<f64>,
+    /// Print the percentage of each slice on it? default: false
+    percentages: Option<bool>,
+}
+
+impl PieChart {
+    fn inner_radius(&self) -> f64 {
+        self.inner_radius.unwrap_or(0.0).clamp(0.0, 1.0)
+    }
+
+    fn percentages(&self) -> bool {
+        self.percentages.unwrap_or(false)
+    }
+
+    /// Colour of each of `n` slices
+    fn slice_colours(&self, n: usize, mut citer: PaletteIter) -> Vec<RGBAColor> {
+        (0..n)
+            .map(|i| {
+                self.colours
+                    .as_ref()
+                    .and_then(|cs| cs.get(i))
+                    .map(|c| c.as_rgba())
+                    .unwrap_or_else(|| citer.next().unwrap())
+            })
+            .collect()
+    }
+}
+
+/// Points along the arc from `start` to `end` (radians) on a unit circle
+fn arc(start: f64, end: f64, radius: f64) -> impl Iterator<Item = (f64, f64)> {
+    let steps = (((end - start).abs() / (2.0 * PI)) * 100.0).ceil().max(2.0) as usize;
+    (0..=steps).map(move |i| {
+        let a = start + (end - start) * (i as f64 / steps as f64);
+        (radius * a.cos(), radius * a.sin())
+    })
+}
+
+/// Sum of the slices, which have to be positive and add up to more than 0
+fn total(values: &[f64]) -> Result<f64> {
+    if let Some(v) = values.iter().find(|v| **v < 0.0) {
+        return Err(render::Error::InvalidChart(format!(
+            "pie chart values cannot be negative, got {}",
+            v
+        )));
+    }
+    let total: f64 = values.iter().sum();
+    if total <= 0.0 {
+        return Err(render::Error::InvalidChart(
+            "pie chart values add up to 0, there are no slices to draw".to_owned(),
+        ));
+    }
+    Ok(total)
+}
+
+/// Name of each slice, there has to be a category for each value
+fn slice_names<'a>(categories: &'a [String], values: &[f64]) -> Result<&'a [String]> {
+    if categories.len() != values.len() {
+        return Err(render::Error::InvalidChart(format!(
+            "pie chart has {} categories but {} values, it needs one for each slice",
+            categories.len(),
+            values.len()
+        )));
+    }
+    Ok(categories)
+}
+
+/// Start and end angle (radians) of each slice, clockwise from the top
+fn slice_angles(values: &[f64]) -> Vec<(f64, f64)> {
+    let total: f64 = values.iter().sum();
+    let mut start = PI / 2.0;
+    values
+        .iter()
+        .map(|v| {
+            let end = start - v / total * 2.0 * PI;
+            let slice = (start, end);
+            start = end;
+            slice
+        })
+        .collect()
+}
+
+impl Render for Chart<PieChart, PiePoint> {
+    type Error = render::Error;
+
+    fn render<DB: DrawingBackend>(&self, root: &DrawingArea<DB, Shift>) -> Result<()> {
+        let dset = match self.info.datasets.as_slice() {
+            [dset] => dset,
+            dsets => {
+                return Err(render::Error::InvalidChart(format!(
+                    "pie charts take exactly one dataset, got {}",
+                    dsets.len()
+                )))
+            }
+        };
+        let total = total(&dset.values)?;
+        let names = slice_names(&self.extra.categories, &dset.values)?;
+        let colours = self
+            .extra
+            .slice_colours(dset.values.len(), self.info.colours());
+        let legend = self.info.legend();
+        let entries: Vec<_> = colours
+            .iter()
+            .zip(names)
+            .map(|(colour, name)| {
+                let colour = *colour;
+                LegendEntry::new(name.clone(), move |pt| legend_for(pt, colour))
+            })
+            .collect();
+        let (area, outside) = legend.split(root, &entries, self.info.theme())?;
+        let caption_font = self.info.caption_font();
+        let mut c = ChartBuilder::on(&area);
+        self.info.apply_margins(&mut c);
+        self.info.apply_caption(&mut c, &caption_font);
+        let mut chart = c.build_cartesian_2d(-1.0..1.0, -1.0..1.0)?;
+
+        // scale the unit circle so it stays round whatever the aspect ratio of the plot
+        let (w, h) = chart.plotting_area().dim_in_pixel();
+        let side = w.min(h) as f64;
+        let scale = (side / w as f64, side / h as f64);
+        let to_coord = move |(x, y): (f64, f64)| (x * scale.0, y * scale.1);
+        let inner = self.extra.inner_radius();
+
+        let fiinfo = self.info.font();
+        let tfont = fiinfo
+            .to_text_style()
+            .pos(Pos::new(HPos::Center, VPos::Center));
+        for ((start, end), colour) in slice_angles(&dset.values).into_iter().zip(&colours) {
+            let points: Vec<_> = if inner > 0.0 {
+                arc(start, end, 1.0).chain(arc(end, start, inner)).collect()
+            } else {
+                arc(start, end, 1.0).chain([(0.0, 0.0)]).collect()
+            };
+            chart.draw_series([Polygon::new(
+                points.into_iter().map(to_coord).collect::<Vec<_>>(),
+                colour.filled(),
+            )])?;
+        }
+        if self.extra.percentages() {
+            chart.draw_series(
+                slice_angles(&dset.values)
+                    .into_iter()
+                    .zip(dset.values.iter().zip(&colours))
+                    .filter(|(_, (v, _))| **v > 0.0)
+                    .map(|((start, end), (v, colour))| {
+                        let mid = (start + end) / 2.0;
+                        let r = (1.0 + inner) / 2.0;
+                        Text::new(
+                            format!("{:.1}%", v / total * 100.0),
+                            to_coord((r * mid.cos(), r * mid.sin())),
+                            // on the slice, so in whichever of black or white is readable on it
+                            tfont.color(contrasting(*colour)),
+                        )
+                    }),
+            )?;
+        }
+        self.info.draw_annotations(&chart)?;
+        legend.draw(
+            &chart.plotting_area().strip_coord_spec(),
+            outside.as_ref(),
+            &entries,
+            self.info.theme(),
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slice_angles() {
+        let angles = slice_angles(&[1.0, 1.0, 2.0]);
+        let expected = [
+            (PI / 2.0, 0.0),
+            (0.0, -PI / 2.0),
+            (-PI / 2.0, -3.0 * PI / 2.0),
+        ];
+        for ((s, e), (es, ee)) in angles.into_iter().zip(expected) {
+            assert!((s - es).abs() < 1e-9 && (e - ee).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_total() {
+        assert_eq!(total(&[1.0, 2.0]).unwrap(), 3.0);
+        assert!(total(&[1.0, -1.0]).is_err());
+        assert!(total(&[0.0, 0.0]).is_err());
+        assert!(total(&[]).is_err());
+    }
+
+    #[test]
+    fn test_slice_names() {
+        let categories = ["a".to_owned(), "b".to_owned()];
+        assert_eq!(slice_names(&categories, &[1.0, 2.0]).unwrap(), categories);
+        assert!(slice_names(&categories, &[1.0]).is_err());
+        assert!(slice_names(&categories, &[1.0, 2.0, 3.0]).is_err());
+    }
+}
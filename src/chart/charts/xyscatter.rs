@@ -1,17 +1,357 @@
+use std::ops::Range;
+
 use crate::{
-    chart::{ChartInfo, ChartType},
-    palette::colours,
+    chart::{
+        annotation::{AnnotationAxis, AxisValue},
+        axis::AxisOptions,
+        error_bar::{ErrorBar, Whisker},
+        legend::LegendEntry,
+        line::{Area, LineStyle},
+        scale::{self, Scale, ScaledAxis},
+        time::{self, TimeAxis},
+        value_label::{LabelPosition, ValueLabels},
+        Chart, ChartInfo, Dataset,
+    },
+    render::{self, Render},
 };
 
 use super::{legend_for, Result, XY};
 
 use plotters::{
-    prelude::{Cartesian2d, ChartBuilder, ChartContext, DrawingBackend},
-    series::LineSeries,
-    style::{Color, FontFamily, WHITE},
+    coord::{
+        ranged1d::{KeyPointHint, NoDefaultFormatting, ValueFormatter},
+        Shift,
+    },
+    element::{Drawable, DynElement, IntoDynElement, PointCollection},
+    prelude::{ChartBuilder, DrawingArea, DrawingBackend, LabelAreaPosition, Polygon, Ranged},
+    style::{Color, RGBAColor, ShapeStyle},
 };
+use plotters_backend::{BackendCoord, DrawingErrorKind};
 use serde::Deserialize;
 
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Fill {
+    /// Just the lines
+    #[default]
+    None,
+    /// Fill between each line and the baseline
+    Baseline,
+    /// Stack each dataset on top of the previous one and fill between them
+    Stacked,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+pub enum Style {
+    /// Join the points with a line
+    #[default]
+    #[serde(rename = "line")]
+    Line,
+    /// Draw a marker at each point
+    #[serde(rename = "points")]
+    Points,
+    /// Both of the above
+    #[serde(rename = "line+points")]
+    LinePoints,
+}
+
+impl Style {
+    fn has_line(self) -> bool {
+        self != Style::Points
+    }
+    fn has_points(self) -> bool {
+        self != Style::Line
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MarkerShape {
+    #[default]
+    Circle,
+    Square,
+    Triangle,
+    Cross,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct Marker {
+    /// default: circle
+    shape: Option<MarkerShape>,
+    /// Radius of the marker in pixels, default: 3
+    size: Option<f64>,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ErrorStyle {
+    /// A whisker at each point with an error
+    #[default]
+    Bars,
+    /// Shade between the ends of the errors
+    Band,
+    /// Both of the above
+    Both,
+}
+
+impl ErrorStyle {
+    fn has_bars(self) -> bool {
+        self != ErrorStyle::Band
+    }
+    fn has_band(self) -> bool {
+        self != ErrorStyle::Bars
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum YAxis {
+    /// The y axis on the left
+    #[default]
+    Primary,
+    /// The y axis on the right
+    Secondary,
+}
+
+/// Options for each dataset of an xy-scatter
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct XYDataset {
+    /// How to draw the points, default: line
+    style: Option<Style>,
+    /// Marker drawn at each point if the style has points
+    marker: Option<Marker>,
+    /// Line joining the points if the style has a line
+    line: Option<LineStyle>,
+    /// y axis the dataset is plotted against, default: primary
+    axis: Option<YAxis>,
+    /// How to show the errors of points that have them, default: bars
+    errors: Option<ErrorStyle>,
+    /// Print the y value next to each point, default: the chart's labels
+    labels: Option<ValueLabels>,
+}
+
+impl XYDataset {
+    fn style(&self) -> Style {
+        self.style.unwrap_or_default()
+    }
+
+    fn axis(&self) -> YAxis {
+        self.axis.unwrap_or_default()
+    }
+
+    fn errors(&self) -> ErrorStyle {
+        self.errors.unwrap_or_default()
+    }
+
+    fn marker_shape(&self) -> MarkerShape {
+        self.marker
+            .as_ref()
+            .and_then(|m| m.shape)
+            .unwrap_or_default()
+    }
+
+    fn marker_size(&self) -> f64 {
+        self.marker.as_ref().and_then(|m| m.size).unwrap_or(3.0)
+    }
+
+    fn line(&self) -> LineStyle {
+        self.line.clone().unwrap_or_default()
+    }
+
+    fn line_width(&self) -> u32 {
+        self.line().width()
+    }
+
+    /// Marker to draw at `pt`, if the style has points
+    fn marker<C>(&self, pt: C, c: RGBAColor) -> Option<PointMarker<C>> {
+        self.style().has_points().then(|| PointMarker {
+            pt,
+            shape: self.marker_shape(),
+            size: self.marker_size(),
+            style: match self.marker_shape() {
+                MarkerShape::Cross => c.stroke_width(self.line_width()),
+                _ => c.filled(),
+            },
+        })
+    }
+
+    /// Legend entry showing a short stretch of the line and/or a marker
+    fn glyph(&self, pt: BackendCoord, c: RGBAColor) -> Glyph {
+        Glyph {
+            pt,
+            line: self.style().has_line().then(|| (self.line(), c)),
+            marker: self.marker((0, 0), c),
+        }
+    }
+}
+
+/// Marker for a single point. Drawn directly on the backend since plotters' own markers can't be
+/// mixed in one series
+struct PointMarker<C> {
+    pt: C,
+    shape: MarkerShape,
+    size: f64,
+    style: ShapeStyle,
+}
+
+impl<C> PointMarker<C> {
+    fn draw_at<DB: DrawingBackend>(
+        &self,
+        (x, y): BackendCoord,
+        backend: &mut DB,
+    ) -> std::result::Result<(), DrawingErrorKind<DB::ErrorType>> {
+        let s = self.size;
+        let at = |dx: f64, dy: f64| (x + dx.round() as i32, y + dy.round() as i32);
+        match self.shape {
+            MarkerShape::Circle => backend.draw_circle((x, y), s.round() as u32, &self.style, true),
+            MarkerShape::Square => backend.draw_rect(at(-s, -s), at(s, s), &self.style, true),
+            MarkerShape::Triangle => {
+                let w = s * 3f64.sqrt() / 2.0;
+                backend.fill_polygon([at(0.0, -s), at(-w, s / 2.0), at(w, s / 2.0)], &self.style)
+            }
+            MarkerShape::Cross => {
+                backend.draw_line(at(-s, -s), at(s, s), &self.style)?;
+                backend.draw_line(at(-s, s), at(s, -s), &self.style)
+            }
+        }
+    }
+}
+
+impl<'a, C> PointCollection<'a, C> for &'a PointMarker<C> {
+    type Point = &'a C;
+    type IntoIter = std::iter::Once<&'a C>;
+    fn point_iter(self) -> Self::IntoIter {
+        std::iter::once(&self.pt)
+    }
+}
+
+impl<C, DB: DrawingBackend> Drawable<DB> for PointMarker<C> {
+    fn draw<I: Iterator<Item = BackendCoord>>(
+        &self,
+        mut pos: I,
+        backend: &mut DB,
+        _: (u32, u32),
+    ) -> std::result::Result<(), DrawingErrorKind<DB::ErrorType>> {
+        match pos.next() {
+            Some(pt) => self.draw_at(pt, backend),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Legend entry for a dataset, matching how it is drawn on the chart
+struct Glyph {
+    pt: BackendCoord,
+    line: Option<(LineStyle, RGBAColor)>,
+    marker: Option<PointMarker<BackendCoord>>,
+}
+
+impl<'a> PointCollection<'a, BackendCoord> for &'a Glyph {
+    type Point = &'a BackendCoord;
+    type IntoIter = std::iter::Once<&'a BackendCoord>;
+    fn point_iter(self) -> Self::IntoIter {
+        std::iter::once(&self.pt)
+    }
+}
+
+impl<DB: DrawingBackend> Drawable<DB> for Glyph {
+    fn draw<I: Iterator<Item = BackendCoord>>(
+        &self,
+        mut pos: I,
+        backend: &mut DB,
+        _: (u32, u32),
+    ) -> std::result::Result<(), DrawingErrorKind<DB::ErrorType>> {
+        let Some((x, y)) = pos.next() else {
+            return Ok(());
+        };
+        if let Some((style, c)) = &self.line {
+            style.draw(&[(x - 5, y), (x + 20, y)], *c, backend)?;
+        }
+        if let Some(marker) = &self.marker {
+            marker.draw_at((x + 7, y), backend)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum XType {
+    /// Plain numbers
+    #[default]
+    Number,
+    /// ISO-8601 dates or date/times, numbers are taken as unix timestamps
+    Datetime,
+}
+
+/// x value of a point as it appears in the spec
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+pub enum XValue {
+    Number(f64),
+    Text(String),
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct XYPoint {
+    x: XValue,
+    y: f64,
+    /// Error of the y value
+    #[serde(flatten)]
+    error: ErrorBar,
+}
+
+/// The x axis, either numeric or date/time. Date/times are in seconds since the epoch
+pub enum XAxis {
+    Scaled(ScaledAxis),
+    Time(TimeAxis),
+}
+
+impl ValueFormatter<f64> for XAxis {
+    fn format_ext(&self, value: &f64) -> String {
+        match self {
+            XAxis::Scaled(a) => a.format_ext(value),
+            XAxis::Time(a) => a.format_ext(value),
+        }
+    }
+}
+
+impl Ranged for XAxis {
+    type FormatOption = NoDefaultFormatting;
+
+    type ValueType = f64;
+
+    fn map(&self, value: &f64, limit: (i32, i32)) -> i32 {
+        match self {
+            XAxis::Scaled(a) => a.map(value, limit),
+            XAxis::Time(a) => a.map(value, limit),
+        }
+    }
+
+    fn key_points<Hint: KeyPointHint>(&self, hint: Hint) -> Vec<f64> {
+        match self {
+            XAxis::Scaled(a) => a.key_points(hint),
+            XAxis::Time(a) => a.key_points(hint),
+        }
+    }
+
+    fn range(&self) -> std::ops::Range<f64> {
+        match self {
+            XAxis::Scaled(a) => a.range(),
+            XAxis::Time(a) => a.range(),
+        }
+    }
+}
+
+impl AnnotationAxis for XAxis {
+    fn position(&self, v: &AxisValue) -> Option<f64> {
+        match self {
+            XAxis::Scaled(a) => a.position(v),
+            XAxis::Time(a) => a.position(v),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Deserialize)]
 pub struct XYScatter {
     /// Labels for the axis
@@ -20,50 +360,480 @@ pub struct XYScatter {
     grid: Option<XY<bool>>,
     /// Margin around plot (between plot and labels)
     margin: Option<XY<f64>>,
+    /// What the x values are, default: datetime if any are strings, otherwise number
+    x_type: Option<XType>,
+    /// strftime format for the x axis labels of a datetime axis, default: depends on the range
+    x_format: Option<String>,
+    /// Scale of the x axis, default: linear
+    x_scale: Option<Scale>,
+    /// Scale of the y axis, default: linear
+    y_scale: Option<Scale>,
+    /// Range and ticks of the x axis. For a datetime axis the values are unix timestamps and
+    /// the labels use x_format instead of the tick format
+    x_axis: Option<AxisOptions>,
+    /// Range and ticks of the y axis
+    y_axis: Option<AxisOptions>,
+    /// Label for the secondary y axis, on the right
+    y2_label: Option<String>,
+    /// Scale of the secondary y axis, default: linear
+    y2_scale: Option<Scale>,
+    /// Range and ticks of the secondary y axis
+    y2_axis: Option<AxisOptions>,
+    /// Fill the area under the lines? default: none
+    fill: Option<Fill>,
+    /// Opacity of the filled area, default: 0.3
+    fill_opacity: Option<f64>,
+    /// y value the filled area goes down to, default: 0
+    baseline: Option<f64>,
+    /// When stacking, interpolate datasets at x values they are missing rather than erroring.
+    /// default: false
+    interpolate: Option<bool>,
+    /// Print the y value next to each point of every dataset, default: none
+    labels: Option<ValueLabels>,
 }
 
 impl XYScatter {
     fn margin(&self) -> XY<f64> {
         self.margin.to_owned().unwrap_or(XY { x: 8.0, y: 10.0 })
     }
+
+    fn x_type(&self, datasets: &[Dataset<XYPoint, XYDataset>]) -> XType {
+        self.x_type.unwrap_or_else(|| {
+            let text = datasets
+                .iter()
+                .flat_map(|d| &d.values)
+                .any(|v| matches!(v.x, XValue::Text(_)));
+            if text {
+                XType::Datetime
+            } else {
+                XType::Number
+            }
+        })
+    }
+
+    /// Datasets with their x values turned into numbers, and the line of each
+    #[allow(clippy::type_complexity)]
+    fn resolved_lines(
+        &self,
+        info: &ChartInfo<XYPoint, XYDataset>,
+        x_type: XType,
+    ) -> Result<(Vec<Dataset<XY<f64>, XYDataset>>, Vec<Line>)> {
+        let datasets = info
+            .datasets
+            .iter()
+            .map(|d| self.resolve(d, x_type))
+            .collect::<Result<Vec<_>>>()?;
+        let mut lines = self.side_lines(&datasets)?;
+        for ((orig, dset), line) in info.datasets.iter().zip(&datasets).zip(&mut lines) {
+            line.add_errors(&dset.extra.name, &orig.values, &dset.values)?;
+        }
+        Ok((datasets, lines))
+    }
+
+    /// Ranges of the x and primary y axes
+    fn ranges(&self, info: &ChartInfo<XYPoint, XYDataset>) -> Result<XY<Range<f64>>> {
+        let x_type = self.x_type(&info.datasets);
+        let (datasets, lines) = self.resolved_lines(info, x_type)?;
+        let x_scale = match x_type {
+            XType::Number => self.x_scale(),
+            XType::Datetime => Scale::Linear,
+        };
+        let x = self
+            .x_axis()
+            .range(x_scale, self.bounds(&lines, x_type).x, 0.0)?;
+        let primary: Vec<_> = datasets
+            .iter()
+            .zip(lines)
+            .filter(|(d, _)| d.options.axis() == YAxis::Primary)
+            .map(|(_, l)| l)
+            .collect();
+        let y = self
+            .y_axis()
+            .range(self.y_scale(), self.bounds(&primary, x_type).y, 0.0)?;
+        Ok(XY { x, y })
+    }
+
+    /// Fix the x and primary y axes to these ranges, where given
+    fn set_ranges(&mut self, x: Option<Range<f64>>, y: Option<Range<f64>>) {
+        if let Some(x) = x {
+            self.x_axis = Some(self.x_axis().with_range(x));
+        }
+        if let Some(y) = y {
+            self.y_axis = Some(self.y_axis().with_range(y));
+        }
+    }
+
+    /// Dataset with its x values turned into numbers
+    fn resolve(
+        &self,
+        d: &Dataset<XYPoint, XYDataset>,
+        x_type: XType,
+    ) -> Result<Dataset<XY<f64>, XYDataset>> {
+        let values = d
+            .values
+            .iter()
+            .map(|v| {
+                let x = match (&v.x, x_type) {
+                    (XValue::Number(x), _) => *x,
+                    (XValue::Text(s), XType::Datetime) => {
+                        time::parse_datetime(s).ok_or_else(|| {
+                            render::Error::InvalidChart(format!(
+                                "x value '{}' in dataset '{}' is not an ISO-8601 date or date/time",
+                                s, d.extra.name
+                            ))
+                        })?
+                    }
+                    (XValue::Text(s), XType::Number) => {
+                        return Err(render::Error::InvalidChart(format!(
+                            "x value '{}' in dataset '{}' is not a number",
+                            s, d.extra.name
+                        )))
+                    }
+                };
+                Ok(XY::new(x, v.y))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Dataset {
+            values,
+            extra: d.extra.clone(),
+            options: d.options.clone(),
+        })
+    }
+
+    fn x_scale(&self) -> Scale {
+        self.x_scale.unwrap_or_default()
+    }
+
+    fn y_scale(&self) -> Scale {
+        self.y_scale.unwrap_or_default()
+    }
+
+    fn x_axis(&self) -> AxisOptions {
+        self.x_axis.clone().unwrap_or_default()
+    }
+
+    fn y_axis(&self) -> AxisOptions {
+        self.y_axis.clone().unwrap_or_default()
+    }
+
+    fn y2_scale(&self) -> Scale {
+        self.y2_scale.unwrap_or_default()
+    }
+
+    fn y2_axis(&self) -> AxisOptions {
+        self.y2_axis.clone().unwrap_or_default()
+    }
+
+    /// Scale of the y axis `side`
+    fn scale_of(&self, side: YAxis) -> Scale {
+        match side {
+            YAxis::Primary => self.y_scale(),
+            YAxis::Secondary => self.y2_scale(),
+        }
+    }
+
+    fn fill(&self) -> Fill {
+        self.fill.unwrap_or_default()
+    }
+
+    fn fill_opacity(&self) -> f64 {
+        self.fill_opacity.unwrap_or(0.3)
+    }
+
+    fn baseline(&self) -> f64 {
+        self.baseline.unwrap_or(0.0)
+    }
+
+    fn interpolate(&self) -> bool {
+        self.interpolate.unwrap_or(false)
+    }
+
+    /// Labels to print on the points of a dataset, if any
+    fn labels<'a>(&'a self, opts: &'a XYDataset) -> Option<&'a ValueLabels> {
+        opts.labels
+            .as_ref()
+            .or(self.labels.as_ref())
+            .filter(|l| l.show())
+    }
+
+    /// Points of the line for each dataset, and the points of the lower edge of their fill
+    fn lines(&self, datasets: &[Dataset<XY<f64>, XYDataset>]) -> Result<Vec<Line>> {
+        let baseline = |pts: &[(f64, f64)]| {
+            pts.iter()
+                .map(|(x, _)| (*x, self.baseline()))
+                .collect::<Vec<_>>()
+        };
+        match self.fill() {
+            Fill::None | Fill::Baseline => Ok(datasets
+                .iter()
+                .map(|d| {
+                    let top: Vec<_> = d.values.iter().map(|v| (v.x, v.y)).collect();
+                    let bottom = baseline(&top);
+                    Line {
+                        top,
+                        bottom,
+                        errors: Vec::new(),
+                        stacked: false,
+                    }
+                })
+                .collect()),
+            Fill::Stacked => {
+                let mut xs: Vec<_> = datasets
+                    .iter()
+                    .flat_map(|d| d.values.iter().map(|v| v.x))
+                    .collect();
+                xs.sort_by(f64::total_cmp);
+                xs.dedup();
+                let mut below: Vec<_> = xs.iter().map(|x| (*x, self.baseline())).collect();
+                datasets
+                    .iter()
+                    .map(|d| {
+                        let top = xs
+                            .iter()
+                            .zip(&below)
+                            .map(|(x, (_, base))| {
+                                self.value_at(d, *x)
+                                    .map(|y| (*x, base + y - self.baseline()))
+                            })
+                            .collect::<Result<Vec<_>>>()?;
+                        let bottom = std::mem::replace(&mut below, top.clone());
+                        Ok(Line {
+                            top,
+                            bottom,
+                            errors: Vec::new(),
+                            stacked: true,
+                        })
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// Lines for each dataset, datasets are only stacked on others against the same y axis
+    fn side_lines(&self, datasets: &[Dataset<XY<f64>, XYDataset>]) -> Result<Vec<Line>> {
+        let mut lines: Vec<Option<Line>> = datasets.iter().map(|_| None).collect();
+        for side in [YAxis::Primary, YAxis::Secondary] {
+            let (idxs, dsets): (Vec<_>, Vec<_>) = datasets
+                .iter()
+                .enumerate()
+                .filter(|(_, d)| d.options.axis() == side)
+                .map(|(n, d)| (n, d.clone()))
+                .unzip();
+            for (n, line) in idxs.into_iter().zip(self.lines(&dsets)?) {
+                lines[n] = Some(line);
+            }
+        }
+        Ok(lines.into_iter().flatten().collect())
+    }
+
+    /// Range of the x and y values that need to fit on the chart
+    fn bounds(&self, lines: &[Line], x_type: XType) -> XY<Range<f64>> {
+        let filled = self.fill() != Fill::None;
+        let pts = || {
+            lines.iter().flat_map(|l| {
+                let errors = l
+                    .errors
+                    .iter()
+                    .flat_map(|(x, low, high)| [(*x, *low), (*x, *high)]);
+                l.top
+                    .iter()
+                    .copied()
+                    .chain(l.bottom.iter().copied().filter(|_| filled))
+                    .chain(errors)
+            })
+        };
+        let xs = pts().map(|p| p.0);
+        XY::new(
+            match x_type {
+                XType::Number => scale::span(xs),
+                XType::Datetime => time::span(xs),
+            },
+            scale::span(pts().map(|p| p.1)),
+        )
+    }
+
+    /// y value of a dataset at `x`, interpolating between its neighbours if allowed
+    fn value_at(&self, d: &Dataset<XY<f64>, XYDataset>, x: f64) -> Result<f64> {
+        if let Some(v) = d.values.iter().find(|v| v.x == x) {
+            return Ok(v.y);
+        }
+        if !self.interpolate() {
+            return Err(render::Error::InvalidChart(format!(
+                "dataset '{}' has no value at x = {} to stack, set interpolate to fill it in",
+                d.extra.name, x
+            )));
+        }
+        let before = d
+            .values
+            .iter()
+            .filter(|v| v.x < x)
+            .max_by(|l, r| l.x.total_cmp(&r.x));
+        let after = d
+            .values
+            .iter()
+            .filter(|v| v.x > x)
+            .min_by(|l, r| l.x.total_cmp(&r.x));
+        Ok(match (before, after) {
+            (Some(l), Some(r)) => l.y + (r.y - l.y) * (x - l.x) / (r.x - l.x),
+            // outside of the dataset it adds nothing to the stack
+            _ => self.baseline(),
+        })
+    }
+}
+
+#[derive(Clone)]
+struct Line {
+    top: Vec<(f64, f64)>,
+    bottom: Vec<(f64, f64)>,
+    /// x and the low and high ends of the error at each point that has one
+    errors: Vec<(f64, f64, f64)>,
+    /// `top` has a point at each x of any stacked dataset, rather than one for each point
+    stacked: bool,
+}
+
+impl Line {
+    /// Find the errors of `points` around where they are drawn on the line, `resolved` are the
+    /// same points with numeric x values
+    fn add_errors(&mut self, name: &str, points: &[XYPoint], resolved: &[XY<f64>]) -> Result<()> {
+        for (n, (pt, r)) in points.iter().zip(resolved).enumerate() {
+            let Some((low, high)) = pt
+                .error
+                .amounts(|| format!("point {} in dataset '{}'", n, name))?
+            else {
+                continue;
+            };
+            let y = self.drawn_at(n, r);
+            self.errors.push((r.x, y - low, y + high));
+        }
+        Ok(())
+    }
+
+    /// y value `pt`, the `n`th point of the dataset, is drawn at. Stacked lines are moved up
+    /// from the values and found by x, unstacked points can share an x so are found by position
+    fn drawn_at(&self, n: usize, pt: &XY<f64>) -> f64 {
+        let drawn = if self.stacked {
+            self.top.iter().find(|(x, _)| *x == pt.x)
+        } else {
+            self.top.get(n)
+        };
+        drawn.map_or(pt.y, |(_, y)| *y)
+    }
 }
 
-impl ChartType for XYScatter {
-    type DataPoint = XY<f64>;
-    type X = plotters::coord::types::RangedCoordf64;
-    type Y = plotters::coord::types::RangedCoordf64;
+/// Width of the caps on error bars in pixels
+const ERROR_CAP_WIDTH: i32 = 8;
 
-    fn render_datasets<'a, 'b, DB: DrawingBackend>(
+impl XYScatter {
+    fn render<DB: DrawingBackend>(
         &self,
-        info: &ChartInfo<Self::DataPoint>,
-        c: &mut ChartBuilder<'a, 'b, DB>,
-    ) -> Result<ChartContext<'a, DB, Cartesian2d<Self::X, Self::Y>>> {
+        info: &ChartInfo<XYPoint, XYDataset>,
+        root: &DrawingArea<DB, Shift>,
+    ) -> Result<()> {
         let fiinfo = info.font();
-        let tfont = fiinfo.into_text_style();
+        let tfont = info.theme().text_style(&fiinfo);
+        let (ticks, labels) = (info.tick_font(), info.label_font());
 
-        let (min_x, min_y, max_x, max_y) = info
+        let x_type = self.x_type(&info.datasets);
+        if x_type == XType::Datetime && self.x_scale() != Scale::Linear {
+            return Err(render::Error::InvalidChart(
+                "x_scale can only be linear for datetime x values".to_owned(),
+            ));
+        }
+        let (datasets, lines) = self.resolved_lines(info, x_type)?;
+        let filled = self.fill() != Fill::None;
+        for (dset, line) in datasets.iter().zip(&lines) {
+            let y_scale = self.scale_of(dset.options.axis());
+            for (x, y) in &line.top {
+                let what = |axis| format!("{} value in dataset '{}'", axis, dset.extra.name);
+                self.x_scale().check(*x, || what("x"))?;
+                y_scale.check(*y, || what("y"))?;
+            }
+            for (_, low, _) in &line.errors {
+                y_scale.check(*low, || {
+                    format!("bottom of an error in dataset '{}'", dset.extra.name)
+                })?;
+            }
+            if filled {
+                y_scale.check(self.baseline(), || "fill baseline".to_owned())?;
+            }
+        }
+        let on = |side: YAxis| -> Vec<Line> {
+            datasets
+                .iter()
+                .zip(&lines)
+                .filter(|(d, _)| d.options.axis() == side)
+                .map(|(_, l)| l.clone())
+                .collect()
+        };
+        let has_secondary = !on(YAxis::Secondary).is_empty();
+        let x_bounds = self.bounds(&lines, x_type).x;
+        let x_axis = || -> Result<XAxis> {
+            Ok(match x_type {
+                XType::Number => XAxis::Scaled(self.x_axis().scaled(
+                    self.x_scale(),
+                    x_bounds.clone(),
+                    0.0,
+                )?),
+                XType::Datetime => XAxis::Time(
+                    TimeAxis::new(
+                        self.x_axis().range(Scale::Linear, x_bounds.clone(), 0.0)?,
+                        self.x_format.clone(),
+                    )?
+                    .with_ticks(self.x_axis().ticks()),
+                ),
+            })
+        };
+        let y_axis = self.y_axis().scaled(
+            self.y_scale(),
+            self.bounds(&on(YAxis::Primary), x_type).y,
+            0.0,
+        )?;
+        // the secondary axis isn't drawn without datasets on it, so its options don't apply
+        let y2_axis = if has_secondary {
+            self.y2_axis().scaled(
+                self.y2_scale(),
+                self.bounds(&on(YAxis::Secondary), x_type).y,
+                0.0,
+            )?
+        } else {
+            ScaledAxis::new(Scale::Linear, 0.0..1.0)
+        };
+        let colours = info.dataset_colours();
+        let legend = info.legend();
+        let entries: Vec<_> = info
             .datasets
             .iter()
-            .map(|s| {
-                let max_x = s.values.iter().map(|v| v.x as u64).max().unwrap();
-                let max_y = s.values.iter().map(|v| v.y as u64).max().unwrap();
-                let min_x = s.values.iter().map(|v| v.x as u64).min().unwrap();
-                let min_y = s.values.iter().map(|v| v.y as u64).min().unwrap();
-                (min_x, min_y, max_x, max_y)
+            .zip(&colours)
+            .map(|(dset, c)| {
+                let (c, opts) = (*c, dset.options.clone());
+                let name = match opts.axis() {
+                    YAxis::Primary => dset.extra.name.clone(),
+                    YAxis::Secondary => format!("{} (right)", dset.extra.name),
+                };
+                if filled {
+                    LegendEntry::new(name, move |pt| legend_for(pt, c))
+                } else {
+                    LegendEntry::new(name, move |pt| opts.glyph(pt, c))
+                }
             })
-            .reduce(|(lmix, lmiy, lmx, lmy), (rmix, rmiy, rmx, rmy)| {
-                (lmix.min(rmix), lmiy.min(rmiy), lmx.max(rmx), lmy.max(rmy))
-            })
-            .unwrap();
+            .collect();
+        let (area, outside) = legend.split(root, &entries, info.theme())?;
         let margin = self.margin();
-        let mut chart = c
-            .set_left_and_bottom_label_area_size(50)
-            .caption(info.caption(), FontFamily::SansSerif)
+        let caption_font = info.caption_font();
+        let mut c = ChartBuilder::on(&area);
+        info.apply_margins(&mut c);
+        info.apply_caption(&mut c, &caption_font);
+        c.set_left_and_bottom_label_area_size(50)
             .margin_left(margin.x)
             .margin_bottom(margin.y)
             .margin_right(10.0 + margin.x)
-            .margin_top(10.0 + margin.y)
-            .build_cartesian_2d(min_x as f64..max_x as f64, min_y as f64..max_y as f64)?;
+            .margin_top(10.0 + margin.y);
+        if has_secondary {
+            c.set_label_area_size(LabelAreaPosition::Right, 50);
+        }
+        let mut chart = c
+            .build_cartesian_2d(x_axis()?, y_axis)?
+            .set_secondary_coord(x_axis()?, y2_axis);
         let mut mesh = chart.configure_mesh();
         let grid = self.grid.clone().unwrap_or(XY::new(false, true));
         if !grid.x {
@@ -72,26 +842,265 @@ impl ChartType for XYScatter {
         if !grid.y {
             mesh.disable_y_mesh();
         }
-        mesh.bold_line_style(&WHITE.mix(0.3))
-            .x_desc(self.axis.x.clone())
+        info.theme().style_mesh(&mut mesh, &ticks, &labels);
+        mesh.x_desc(self.axis.x.clone())
             .y_desc(self.axis.y.clone())
-            .label_style(tfont.clone())
             .draw()?;
-        let mut citer = colours();
-        for dset in &info.datasets {
-            let c = dset
-                .extra
-                .colour
-                .map(|c| c.as_rgba())
-                .unwrap_or_else(|| citer.next().unwrap().to_rgba());
-            chart
-                .draw_series(LineSeries::new(dset.values.iter().map(|v| (v.x, v.y)), c))?
-                .label(dset.extra.name.clone())
-                .legend(move |pt| legend_for(pt, c));
+        if has_secondary {
+            let mut mesh = chart.configure_secondary_axes();
+            info.theme()
+                .style_secondary_mesh(&mut mesh, &ticks, &labels);
+            mesh.y_desc(self.y2_label.clone().unwrap_or_default())
+                .draw()?;
         }
-        Ok(chart)
+        for ((dset, line), c) in datasets.iter().zip(&lines).zip(colours) {
+            let opts = &dset.options;
+            let mut elems: Vec<DynElement<DB, (f64, f64)>> = Vec::new();
+            if filled {
+                elems.push(
+                    Area::new(
+                        line.top.clone(),
+                        line.bottom.clone(),
+                        opts.line().interpolation(),
+                        c.mix(self.fill_opacity()).filled(),
+                    )
+                    .into_dyn(),
+                );
+            }
+            if !line.errors.is_empty() && opts.errors().has_band() {
+                let mut band: Vec<_> = line.errors.iter().map(|(x, _, h)| (*x, *h)).collect();
+                band.extend(line.errors.iter().rev().map(|(x, l, _)| (*x, *l)));
+                elems.push(Polygon::new(band, c.mix(self.fill_opacity()).filled()).into_dyn());
+            }
+            if opts.errors().has_bars() {
+                elems.extend(line.errors.iter().map(|(x, low, high)| {
+                    Whisker::new(
+                        (*x, *low),
+                        (*x, *high),
+                        ERROR_CAP_WIDTH,
+                        c.stroke_width(opts.line_width()),
+                    )
+                    .into_dyn()
+                }));
+            }
+            if opts.style().has_line() {
+                elems.push(opts.line().element(line.top.clone(), c).into_dyn());
+            }
+            // only at the dataset's own points, stacked lines can have more to follow the others
+            elems.extend(
+                dset.values
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(n, pt)| opts.marker((pt.x, line.drawn_at(n, pt)), c))
+                    .map(|m| m.into_dyn()),
+            );
+            match opts.axis() {
+                YAxis::Primary => chart.draw_series(elems)?,
+                YAxis::Secondary => chart.draw_secondary_series(elems)?,
+            };
+        }
+        let area = chart.plotting_area().strip_coord_spec();
+        for (dset, line) in datasets.iter().zip(&lines) {
+            let Some(labels) = self.labels(&dset.options) else {
+                continue;
+            };
+            for (n, pt) in dset.values.iter().enumerate() {
+                let at = (pt.x, line.drawn_at(n, pt));
+                let at = match dset.options.axis() {
+                    YAxis::Primary => chart.backend_coord(&at),
+                    YAxis::Secondary => chart.borrow_secondary().backend_coord(&at),
+                };
+                labels.draw(
+                    &area,
+                    at,
+                    (0.0, -1.0),
+                    pt.y,
+                    &tfont,
+                    LabelPosition::Outside,
+                    None,
+                )?;
+            }
+        }
+        info.draw_annotations(&chart)?;
+        legend.draw(
+            &chart.plotting_area().strip_coord_spec(),
+            outside.as_ref(),
+            &entries,
+            info.theme(),
+        )?;
+        Ok(())
+    }
+}
+
+impl Chart<XYScatter, XYPoint, XYDataset> {
+    /// Ranges of the x and primary y axes
+    pub fn axis_ranges(&self) -> Result<XY<Range<f64>>> {
+        self.extra.ranges(&self.info)
+    }
+
+    /// Fix the x and primary y axes to these ranges, where given
+    pub fn set_axis_ranges(&mut self, x: Option<Range<f64>>, y: Option<Range<f64>>) {
+        self.extra.set_ranges(x, y)
+    }
+}
+
+impl Render for Chart<XYScatter, XYPoint, XYDataset> {
+    type Error = render::Error;
+
+    fn render<DB: DrawingBackend>(&self, root: &DrawingArea<DB, Shift>) -> Result<()> {
+        self.extra.render(&self.info, root)
     }
 }
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use super::*;
+    use crate::chart::{Chart, DatasetMeta};
+    use plotters::backend::SVGBackend;
+    use plotters::prelude::IntoDrawingArea;
+
+    fn dataset(name: &str, values: &[(f64, f64)]) -> Dataset<XY<f64>, XYDataset> {
+        Dataset {
+            values: values.iter().map(|(x, y)| XY::new(*x, *y)).collect(),
+            extra: DatasetMeta {
+                name: name.to_owned(),
+                colour: None,
+            },
+            options: XYDataset::default(),
+        }
+    }
+
+    fn render_svg(yaml: &str) -> Result<String> {
+        let chart: Chart<XYScatter, XYPoint, XYDataset> = serde_yaml::from_str(yaml).unwrap();
+        let mut svg = String::new();
+        chart.render(&SVGBackend::with_string(&mut svg, (400, 300)).into_drawing_area())?;
+        Ok(svg)
+    }
+
+    fn chart(fill: Fill, interpolate: bool) -> XYScatter {
+        XYScatter {
+            axis: XY::new("", ""),
+            grid: None,
+            margin: None,
+            x_type: None,
+            x_format: None,
+            x_scale: None,
+            y_scale: None,
+            x_axis: None,
+            y_axis: None,
+            y2_label: None,
+            y2_scale: None,
+            y2_axis: None,
+            fill: Some(fill),
+            fill_opacity: None,
+            baseline: None,
+            interpolate: Some(interpolate),
+            labels: None,
+        }
+    }
+
+    #[test]
+    fn test_dataset_options() {
+        let d: Dataset<XY<f64>, XYDataset> = serde_yaml::from_str(
+            "name: a\nvalues: []\nstyle: line+points\nmarker: {shape: cross, size: 5}",
+        )
+        .unwrap();
+        assert_eq!(d.options.style(), Style::LinePoints);
+        assert_eq!(d.options.marker_shape(), MarkerShape::Cross);
+        assert_eq!(d.options.marker_size(), 5.0);
+        assert_eq!(d.options.line_width(), 1);
+    }
+
+    #[test]
+    fn test_bounds() {
+        let c = chart(Fill::None, false);
+        let lines = c
+            .lines(&[dataset("a", &[(-0.5, 0.25), (0.5, -0.75)])])
+            .unwrap();
+        let b = c.bounds(&lines, XType::Number);
+        assert_eq!((b.x, b.y), (-0.5..0.5, -0.75..0.25));
+
+        let lines = c.lines(&[dataset("a", &[(0.0, 0.0), (1.0, 0.0)])]).unwrap();
+        assert_eq!(c.bounds(&lines, XType::Number).y, 0.0..1.0);
+
+        // the errors have to fit too
+        let points: Vec<XYPoint> =
+            serde_yaml::from_str("[{x: 0, y: 1, err: 2}, {x: 1, y: 1}]").unwrap();
+        let mut lines = c.lines(&[dataset("a", &[(0.0, 1.0), (1.0, 1.0)])]).unwrap();
+        lines[0]
+            .add_errors("a", &points, &[XY::new(0.0, 1.0), XY::new(1.0, 1.0)])
+            .unwrap();
+        assert_eq!(lines[0].errors, vec![(0.0, -1.0, 3.0)]);
+        assert_eq!(c.bounds(&lines, XType::Number).y, -1.0..3.0);
+
+        // points with the same x get errors around their own y
+        let points: Vec<XYPoint> =
+            serde_yaml::from_str("[{x: 0, y: 1, err: 1}, {x: 0, y: 5, err: 1}]").unwrap();
+        let resolved = [XY::new(0.0, 1.0), XY::new(0.0, 5.0)];
+        let mut lines = c.lines(&[dataset("a", &[(0.0, 1.0), (0.0, 5.0)])]).unwrap();
+        lines[0].add_errors("a", &points, &resolved).unwrap();
+        assert_eq!(lines[0].errors, vec![(0.0, 0.0, 2.0), (0.0, 4.0, 6.0)]);
+        assert_eq!(lines[0].drawn_at(1, &resolved[1]), 5.0);
+
+        // filled area goes down to the baseline
+        let c = chart(Fill::Baseline, false);
+        let lines = c.lines(&[dataset("a", &[(0.0, 2.0), (1.0, 3.0)])]).unwrap();
+        assert_eq!(c.bounds(&lines, XType::Number).y, 0.0..3.0);
+    }
+
+    #[test]
+    fn test_stacked_lines() {
+        let dsets = [
+            dataset("a", &[(0.0, 1.0), (1.0, 2.0)]),
+            dataset("b", &[(0.0, 3.0), (1.0, 1.0)]),
+        ];
+        let lines = chart(Fill::Stacked, false).lines(&dsets).unwrap();
+        assert_eq!(lines[0].bottom, vec![(0.0, 0.0), (1.0, 0.0)]);
+        assert_eq!(lines[0].top, vec![(0.0, 1.0), (1.0, 2.0)]);
+        assert_eq!(lines[1].bottom, lines[0].top);
+        assert_eq!(lines[1].top, vec![(0.0, 4.0), (1.0, 3.0)]);
+        // stacked points are found by x, as the stack has every dataset's x values
+        assert_eq!(lines[1].drawn_at(0, &XY::new(1.0, 1.0)), 3.0);
+    }
+
+    #[test]
+    fn test_stacked_misaligned() {
+        let dsets = [
+            dataset("a", &[(0.0, 1.0), (2.0, 3.0)]),
+            dataset("b", &[(0.0, 1.0), (1.0, 1.0), (2.0, 1.0)]),
+        ];
+        assert!(chart(Fill::Stacked, false).lines(&dsets).is_err());
+        let lines = chart(Fill::Stacked, true).lines(&dsets).unwrap();
+        assert_eq!(lines[0].top, vec![(0.0, 1.0), (1.0, 2.0), (2.0, 3.0)]);
+        assert_eq!(lines[1].top, vec![(0.0, 2.0), (1.0, 3.0), (2.0, 4.0)]);
+    }
+
+    #[test]
+    fn test_secondary_not_stacked() {
+        let mut dsets = [
+            dataset("a", &[(0.0, 1.0), (1.0, 2.0)]),
+            dataset("b", &[(0.0, 100.0), (1.0, 200.0)]),
+            dataset("c", &[(0.0, 1.0), (1.0, 1.0)]),
+        ];
+        dsets[1].options = serde_yaml::from_str("axis: secondary").unwrap();
+        let lines = chart(Fill::Stacked, false).side_lines(&dsets).unwrap();
+        assert_eq!(lines[1].top, vec![(0.0, 100.0), (1.0, 200.0)]);
+        assert_eq!(lines[2].bottom, lines[0].top);
+        assert_eq!(lines[2].top, vec![(0.0, 2.0), (1.0, 3.0)]);
+    }
+
+    #[test]
+    fn test_unused_secondary_axis() {
+        let spec = "{axis: {x: x, y: y}, y2_scale: log, datasets: [{name: a, values: [{x: 0, y: 0}, {x: 1, y: 1}]}]}";
+        assert!(render_svg(spec).is_ok());
+    }
+
+    #[test]
+    fn test_stacked_markers() {
+        let spec = "{axis: {x: x, y: y}, fill: stacked, interpolate: true, datasets: [\
+            {name: a, style: line+points, values: [{x: 0, y: 1}, {x: 2, y: 1}]}, \
+            {name: b, style: line+points, values: [{x: 1, y: 1}, {x: 2, y: 1}]}]}";
+        let svg = render_svg(spec).unwrap();
+        assert_eq!(svg.matches("<circle").count(), 4);
+    }
+}
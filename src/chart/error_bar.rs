@@ -0,0 +1,113 @@
+use plotters::element::{Drawable, PointCollection};
+use plotters::prelude::DrawingBackend;
+use plotters::style::ShapeStyle;
+use plotters_backend::{BackendCoord, DrawingErrorKind};
+use serde::Deserialize;
+
+use crate::render;
+
+/// Uncertainty of a value, either the same both ways or separately below and above it
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct ErrorBar {
+    /// Amount the value could be off by in either direction
+    err: Option<f64>,
+    /// Amount the value could be below what was given, default: err
+    err_low: Option<f64>,
+    /// Amount the value could be above what was given, default: err
+    err_high: Option<f64>,
+}
+
+impl ErrorBar {
+    /// How far below and above the value the error goes, if there is any. `what` describes the
+    /// value for the error if either amount is negative
+    pub fn amounts(
+        &self,
+        what: impl FnOnce() -> String,
+    ) -> Result<Option<(f64, f64)>, render::Error> {
+        if self.err.is_none() && self.err_low.is_none() && self.err_high.is_none() {
+            return Ok(None);
+        }
+        let low = self.err_low.or(self.err).unwrap_or(0.0);
+        let high = self.err_high.or(self.err).unwrap_or(0.0);
+        if low < 0.0 || high < 0.0 {
+            return Err(render::Error::InvalidChart(format!(
+                "error of {} is negative",
+                what()
+            )));
+        }
+        Ok(Some((low, high)))
+    }
+}
+
+/// Line between the ends of an error bar with a cap across each end
+pub struct Whisker<C> {
+    ends: [C; 2],
+    /// Width of the caps in pixels
+    cap: i32,
+    style: ShapeStyle,
+}
+
+impl<C> Whisker<C> {
+    pub fn new<S: Into<ShapeStyle>>(low: C, high: C, cap: i32, style: S) -> Self {
+        Self {
+            ends: [low, high],
+            cap,
+            style: style.into(),
+        }
+    }
+}
+
+impl<'a, C> PointCollection<'a, C> for &'a Whisker<C> {
+    type Point = &'a C;
+    type IntoIter = &'a [C];
+    fn point_iter(self) -> Self::IntoIter {
+        &self.ends
+    }
+}
+
+impl<C, DB: DrawingBackend> Drawable<DB> for Whisker<C> {
+    fn draw<I: Iterator<Item = BackendCoord>>(
+        &self,
+        mut pos: I,
+        backend: &mut DB,
+        _: (u32, u32),
+    ) -> Result<(), DrawingErrorKind<DB::ErrorType>> {
+        let (Some(low), Some(high)) = (pos.next(), pos.next()) else {
+            return Ok(());
+        };
+        backend.draw_line(low, high, &self.style)?;
+        // caps go across the bar, whichever way it points
+        let across = if (high.1 - low.1).abs() >= (high.0 - low.0).abs() {
+            (self.cap / 2, 0)
+        } else {
+            (0, self.cap / 2)
+        };
+        for (x, y) in [low, high] {
+            backend.draw_line(
+                (x - across.0, y - across.1),
+                (x + across.0, y + across.1),
+                &self.style,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_amounts() {
+        let amounts = |yaml: &str| {
+            serde_yaml::from_str::<ErrorBar>(yaml)
+                .unwrap()
+                .amounts(|| "x".to_owned())
+        };
+        assert_eq!(amounts("{}").unwrap(), None);
+        assert_eq!(amounts("err: 2").unwrap(), Some((2.0, 2.0)));
+        assert_eq!(amounts("{err: 2, err_high: 3}").unwrap(), Some((2.0, 3.0)));
+        assert_eq!(amounts("err_low: 1").unwrap(), Some((1.0, 0.0)));
+        assert!(amounts("err: -1").is_err());
+    }
+}
@@ -0,0 +1,331 @@
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use plotters::coord::Shift;
+use plotters::prelude::{DrawingArea, DrawingBackend};
+use serde::Deserialize;
+
+use super::Charts;
+use crate::render::{self, FontInfo, Render};
+use crate::theme::{Theme, ThemeSpec};
+
+type Result<T> = std::result::Result<T, render::Error>;
+
+/// Which charts in a figure share an axis
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Share {
+    /// Every chart in the figure
+    All,
+    /// Charts starting in the same row
+    Rows,
+    /// Charts starting in the same column
+    Columns,
+}
+
+/// A chart and where it goes in the figure
+#[derive(Clone, Debug, Deserialize)]
+pub struct Cell {
+    /// Row of the top of the chart, counting from 0. default: the first with room
+    row: Option<usize>,
+    /// Column of the left of the chart, counting from 0. default: the first with room
+    column: Option<usize>,
+    /// Rows the chart covers, default: 1
+    row_span: Option<usize>,
+    /// Columns the chart covers, default: 1
+    column_span: Option<usize>,
+    /// Drawn above the chart, outside of it
+    title: Option<String>,
+    #[serde(flatten)]
+    chart: Charts,
+}
+
+impl Cell {
+    fn spans(&self) -> (usize, usize) {
+        (
+            self.row_span.unwrap_or(1).max(1),
+            self.column_span.unwrap_or(1).max(1),
+        )
+    }
+}
+
+/// Several charts laid out in a grid
+#[derive(Clone, Debug, Deserialize)]
+pub struct Figure {
+    /// default: as many as the charts need
+    rows: Option<usize>,
+    /// default: 1
+    columns: Option<usize>,
+    /// Drawn above all of the charts
+    caption: Option<String>,
+    /// Font of the caption and the titles of the charts, default: the theme's font
+    font: Option<FontInfo>,
+    /// Charts with the same x axis range, only xy-scatters can share axes. default: none
+    share_x: Option<Share>,
+    /// Charts with the same primary y axis range. default: none
+    share_y: Option<Share>,
+    /// TTF or OTF files relative to the spec, for the figure and any of its charts
+    font_files: Option<Vec<PathBuf>>,
+    /// Theme of the figure and any of its charts without their own, default: light
+    #[serde(rename = "theme")]
+    theme_spec: Option<ThemeSpec>,
+    #[serde(skip)]
+    theme: Theme,
+    /// Charts in the figure, placed in order
+    charts: Vec<Cell>,
+}
+
+/// Where a chart is in the grid
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Place {
+    row: usize,
+    column: usize,
+    rows: usize,
+    columns: usize,
+}
+
+/// Most rows or columns a figure can have, more charts than this couldn't be made out
+const MAX_CELLS: usize = 100;
+
+/// Place each of `cells` in a grid `columns` wide and `max_rows` high, or [`MAX_CELLS`] high
+fn place(cells: &[Cell], columns: usize, max_rows: Option<usize>) -> Result<Vec<Place>> {
+    let max_rows = max_rows.unwrap_or(MAX_CELLS);
+    if columns > MAX_CELLS || max_rows > MAX_CELLS {
+        return Err(render::Error::InvalidChart(format!(
+            "figure has {} rows and {} columns, it can have at most {} of each",
+            max_rows, columns, MAX_CELLS
+        )));
+    }
+    let mut taken: Vec<Vec<bool>> = Vec::new();
+    let mut places = Vec::with_capacity(cells.len());
+    for (n, cell) in cells.iter().enumerate() {
+        let (rows, cols) = cell.spans();
+        let fits = |taken: &Vec<Vec<bool>>, row: usize, column: usize| {
+            column.checked_add(cols).is_some_and(|end| end <= columns)
+                && row.checked_add(rows).is_some_and(|end| end <= max_rows)
+                && (row..row + rows).all(|r| {
+                    (column..column + cols)
+                        .all(|c| !taken.get(r).is_some_and(|cs: &Vec<bool>| cs[c]))
+                })
+        };
+        let candidates = match cell.row {
+            Some(r) => r..r.saturating_add(1),
+            None => 0..taken.len() + 1,
+        };
+        let columns_to_try = match cell.column {
+            Some(c) => c..c.saturating_add(1),
+            None => 0..columns,
+        };
+        let (row, column) = candidates
+            .flat_map(|r| columns_to_try.clone().map(move |c| (r, c)))
+            .find(|(r, c)| fits(&taken, *r, *c))
+            .ok_or_else(|| {
+                render::Error::InvalidChart(format!(
+                    "no room in the figure for chart {}, it may overlap another or go past the \
+                     last of the {} rows or {} columns",
+                    n, max_rows, columns
+                ))
+            })?;
+        if taken.len() < row + rows {
+            taken.resize(row + rows, vec![false; columns]);
+        }
+        for r in &mut taken[row..row + rows] {
+            r[column..column + cols].fill(true);
+        }
+        places.push(Place {
+            row,
+            column,
+            rows,
+            columns: cols,
+        });
+    }
+    Ok(places)
+}
+
+/// Pixel positions splitting `len` into `n` equal parts, including both ends
+fn breakpoints(len: u32, n: usize) -> Vec<i32> {
+    (0..=n)
+        .map(|i| (i as u64 * len as u64 / n as u64) as i32)
+        .collect()
+}
+
+impl Figure {
+    fn columns(&self) -> usize {
+        self.columns.unwrap_or(1).max(1)
+    }
+
+    fn font(&self) -> FontInfo {
+        self.font.clone().unwrap_or_else(|| self.theme.font())
+    }
+
+    /// See [`super::Chart::load_theme`], charts in the figure without a theme use the figure's
+    pub fn load_theme(
+        &mut self,
+        theme: Option<&Theme>,
+        parent: &Theme,
+        dir: &Path,
+    ) -> anyhow::Result<&Theme> {
+        self.theme = match (theme, &self.theme_spec) {
+            (Some(t), _) => t.clone(),
+            (None, Some(spec)) => spec.load(dir)?,
+            (None, None) => parent.clone(),
+        };
+        for cell in &mut self.charts {
+            cell.chart.load_theme(theme, &self.theme, dir)?;
+        }
+        Ok(&self.theme)
+    }
+
+    /// See [`super::Chart::theme`]
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    /// Font files of the figure and all of its charts
+    pub fn font_files(&self) -> Vec<PathBuf> {
+        let mut files = self.font_files.clone().unwrap_or_default();
+        for cell in &self.charts {
+            files.extend(cell.chart.font_files());
+        }
+        files
+    }
+
+    /// Give the charts sharing an axis the range covering all of them
+    fn share(&self, charts: &mut [Charts], places: &[Place]) -> Result<()> {
+        let ranges = charts
+            .iter()
+            .map(|c| c.axis_ranges().transpose())
+            .collect::<Result<Vec<_>>>()?;
+        for (share, is_x) in [(self.share_x, true), (self.share_y, false)] {
+            let Some(share) = share else {
+                continue;
+            };
+            let group = |p: &Place| match share {
+                Share::All => 0,
+                Share::Rows => p.row,
+                Share::Columns => p.column,
+            };
+            let mut groups: HashMap<usize, Range<f64>> = HashMap::new();
+            for (r, p) in ranges.iter().zip(places) {
+                if let Some(r) = r {
+                    let r = if is_x { r.x.clone() } else { r.y.clone() };
+                    groups
+                        .entry(group(p))
+                        .and_modify(|g| *g = g.start.min(r.start)..g.end.max(r.end))
+                        .or_insert(r);
+                }
+            }
+            for (c, p) in charts.iter_mut().zip(places) {
+                let range = groups.get(&group(p)).cloned();
+                if is_x {
+                    c.set_axis_ranges(range, None)
+                } else {
+                    c.set_axis_ranges(None, range)
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Render for Figure {
+    type Error = render::Error;
+
+    fn render<DB: DrawingBackend>(&self, root: &DrawingArea<DB, Shift>) -> Result<()> {
+        let font = self.font();
+        let style = self.theme.text_style(&font);
+        let area = match &self.caption {
+            Some(caption) => root.titled(caption, style.clone())?,
+            None => root.clone(),
+        };
+        let columns = self.columns();
+        let places = place(&self.charts, columns, self.rows)?;
+        let needed = places.iter().map(|p| p.row + p.rows).max().unwrap_or(1);
+        let rows = self.rows.unwrap_or(needed);
+        let mut charts: Vec<_> = self.charts.iter().map(|c| c.chart.clone()).collect();
+        self.share(&mut charts, &places)?;
+
+        let (w, h) = area.dim_in_pixel();
+        let (xs, ys) = (breakpoints(w, columns), breakpoints(h, rows));
+        for ((cell, chart), p) in self.charts.iter().zip(&charts).zip(&places) {
+            let (left, top) = (xs[p.column], ys[p.row]);
+            let size = (xs[p.column + p.columns] - left, ys[p.row + p.rows] - top);
+            let cell_area = area.clone().shrink((left, top), size);
+            let cell_area = match &cell.title {
+                Some(title) => cell_area.titled(title, style.clone())?,
+                None => cell_area,
+            };
+            cell_area.fill(&chart.theme().background())?;
+            chart.render(&cell_area)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn places(yaml: &str, columns: usize) -> Result<Vec<(usize, usize)>> {
+        let cells: Vec<Cell> = serde_yaml::from_str(yaml).unwrap();
+        Ok(place(&cells, columns, None)?
+            .into_iter()
+            .map(|p| (p.row, p.column))
+            .collect())
+    }
+
+    #[test]
+    fn test_place() {
+        let pie = "{type: pie, categories: [a], datasets: [{name: a, values: [1]}]";
+        let cells = |extra: &[&str]| {
+            let cells: Vec<_> = extra.iter().map(|e| format!("{}{}}}", pie, e)).collect();
+            format!("[{}]", cells.join(", "))
+        };
+        assert_eq!(
+            places(&cells(&["", "", ""]), 2).unwrap(),
+            [(0, 0), (0, 1), (1, 0)]
+        );
+        assert_eq!(
+            places(&cells(&[", column_span: 2", "", ", row: 3"]), 2).unwrap(),
+            [(0, 0), (1, 0), (3, 0)]
+        );
+        assert_eq!(
+            places(&cells(&[", row_span: 2", "", ""]), 2).unwrap(),
+            [(0, 0), (0, 1), (1, 1)]
+        );
+        assert!(places(&cells(&[", column_span: 3"]), 2).is_err());
+        assert!(places(&cells(&[", row: 0, column: 0", ", row: 0, column: 0"]), 2).is_err());
+        assert!(places(&cells(&[", row: 18446744073709551615"]), 2).is_err());
+        assert!(places(&cells(&[", row_span: 18446744073709551615"]), 2).is_err());
+        assert!(places(&cells(&[", column: 18446744073709551615"]), 2).is_err());
+        assert!(places(&cells(&[""]), MAX_CELLS + 1).is_err());
+        let cells: Vec<Cell> = serde_yaml::from_str(&cells(&["", "", ", row: 2"])).unwrap();
+        assert!(place(&cells, 1, Some(2)).is_err());
+        assert_eq!(place(&cells, 1, Some(3)).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_cell_theme() {
+        let mut figure: Figure = serde_yaml::from_str(
+            "{charts: [{type: pie, theme: dark, categories: [a], datasets: [{name: a, values: [1]}]}, \
+             {type: pie, categories: [a], datasets: [{name: a, values: [1]}]}]}",
+        )
+        .unwrap();
+        figure
+            .load_theme(None, &Theme::default(), Path::new("."))
+            .unwrap();
+        let dark = ThemeSpec::Named("dark".to_string())
+            .load(Path::new("."))
+            .unwrap();
+        let backgrounds: Vec<_> = figure
+            .charts
+            .iter()
+            .map(|c| c.chart.theme().background())
+            .collect();
+        assert_eq!(
+            backgrounds,
+            [dark.background(), Theme::default().background()]
+        );
+    }
+}
@@ -0,0 +1,307 @@
+use plotters::coord::Shift;
+use plotters::element::{DynElement, IntoDynElement, Rectangle, Text};
+use plotters::prelude::{DrawingArea, DrawingBackend};
+use plotters::style::text_anchor::{HPos, Pos, VPos};
+use plotters::style::{Color, RGBAColor};
+use plotters_backend::BackendCoord;
+use serde::Deserialize;
+
+use crate::render::{self, CssColour, FontInfo};
+use crate::theme::Theme;
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum LegendPosition {
+    UpperLeft,
+    UpperMiddle,
+    #[default]
+    UpperRight,
+    MiddleLeft,
+    MiddleMiddle,
+    MiddleRight,
+    LowerLeft,
+    LowerMiddle,
+    LowerRight,
+    /// Right of the plot, which is narrowed to make room for it
+    OutsideRight,
+    /// Below the plot, which is shortened to make room for it
+    Below,
+}
+
+impl LegendPosition {
+    fn is_outside(&self) -> bool {
+        matches!(self, Self::OutsideRight | Self::Below)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LegendOrder {
+    /// Order of the datasets, apart from stacked bars which are listed top to bottom
+    #[default]
+    Given,
+    /// Opposite of given
+    Reverse,
+    /// Alphabetical by name
+    Name,
+}
+
+/// Key of the datasets and what they look like
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct Legend {
+    /// Draw the legend? default: true
+    show: Option<bool>,
+    /// default: upper-right
+    position: Option<LegendPosition>,
+    /// Entries per row, default: 1, or all of them when below the plot
+    columns: Option<usize>,
+    /// default: given
+    order: Option<LegendOrder>,
+    /// default: the chart's font
+    font: Option<FontInfo>,
+    /// default: the theme's legend background
+    background: Option<CssColour>,
+    /// default: the theme's legend border
+    border: Option<CssColour>,
+}
+
+/// `legend` can be just whether to show it
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+pub enum LegendSpec {
+    Show(bool),
+    Options(Legend),
+}
+
+impl From<LegendSpec> for Legend {
+    fn from(spec: LegendSpec) -> Self {
+        match spec {
+            LegendSpec::Show(show) => Legend {
+                show: Some(show),
+                ..Default::default()
+            },
+            LegendSpec::Options(l) => l,
+        }
+    }
+}
+
+/// Name of a dataset in the legend and a glyph matching how it is drawn
+pub struct LegendEntry<'a, DB: DrawingBackend> {
+    name: String,
+    #[allow(clippy::type_complexity)]
+    glyph: Box<dyn Fn(BackendCoord) -> DynElement<'a, DB, BackendCoord> + 'a>,
+}
+
+impl<'a, DB: DrawingBackend + 'a> LegendEntry<'a, DB> {
+    /// `glyph` is given a point 5 pixels in from the left of its space, which is 25 pixels wide
+    pub fn new<E, F>(name: impl Into<String>, glyph: F) -> Self
+    where
+        E: IntoDynElement<'a, DB, BackendCoord>,
+        F: Fn(BackendCoord) -> E + 'a,
+    {
+        Self {
+            name: name.into(),
+            glyph: Box::new(move |pt| glyph(pt).into_dyn()),
+        }
+    }
+}
+
+/// Space in pixels between the legend and the edge of whatever it is drawn on
+const EDGE: i32 = 5;
+/// Space in pixels for the glyph before each name
+const GLYPH_WIDTH: i32 = 30;
+
+/// Sizes in pixels of the legend box, for a given set of entries
+struct Layout {
+    columns: Vec<i32>,
+    row_height: i32,
+    padding: i32,
+    size: (i32, i32),
+}
+
+impl Legend {
+    pub fn show(&self) -> bool {
+        self.show.unwrap_or(true)
+    }
+
+    /// Use `font` if the legend doesn't have its own
+    pub fn or_font(mut self, font: FontInfo) -> Self {
+        self.font.get_or_insert(font);
+        self
+    }
+
+    fn position(&self) -> LegendPosition {
+        self.position.unwrap_or_default()
+    }
+
+    fn font(&self, theme: &Theme) -> FontInfo {
+        self.font.clone().unwrap_or_else(|| theme.font())
+    }
+
+    fn background(&self, theme: &Theme) -> RGBAColor {
+        self.background
+            .map(|c| c.as_rgba())
+            .unwrap_or_else(|| theme.legend_background())
+    }
+
+    fn border(&self, theme: &Theme) -> RGBAColor {
+        self.border
+            .map(|c| c.as_rgba())
+            .unwrap_or_else(|| theme.legend_border())
+    }
+
+    fn columns(&self, entries: usize) -> usize {
+        let default = match self.position() {
+            LegendPosition::Below => entries,
+            _ => 1,
+        };
+        self.columns.unwrap_or(default).clamp(1, entries.max(1))
+    }
+
+    /// `entries` in the order they are listed
+    fn arrange<'e, 'a, DB: DrawingBackend>(
+        &self,
+        entries: &'e [LegendEntry<'a, DB>],
+    ) -> Vec<&'e LegendEntry<'a, DB>> {
+        let mut entries: Vec<_> = entries.iter().collect();
+        match self.order.unwrap_or_default() {
+            LegendOrder::Given => {}
+            LegendOrder::Reverse => entries.reverse(),
+            LegendOrder::Name => entries.sort_by(|a, b| a.name.cmp(&b.name)),
+        }
+        entries
+    }
+
+    fn layout(&self, names: &[&str], theme: &Theme) -> Result<Layout, render::Error> {
+        let font = self.font(theme);
+        let style = font.to_text_style();
+        let sizes = names
+            .iter()
+            .map(|n| style.font.box_size(n))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| render::Error::FontLoading(e.to_string()))?;
+        let ncols = self.columns(names.len());
+        let mut columns = vec![0; ncols];
+        for (n, (w, _)) in sizes.iter().enumerate() {
+            columns[n % ncols] = columns[n % ncols].max(GLYPH_WIDTH + *w as i32);
+        }
+        let text_height = sizes
+            .iter()
+            .map(|(_, h)| *h as i32)
+            .max()
+            .unwrap_or(0)
+            .max(font.size.round() as i32);
+        let row_height = (text_height as f64 * 1.5).round() as i32;
+        let padding = text_height;
+        let rows = names.len().div_ceil(ncols) as i32;
+        let width = 2 * padding + columns.iter().sum::<i32>() + (ncols as i32 - 1) * padding * 2;
+        let height = 2 * padding + rows * row_height;
+        Ok(Layout {
+            columns,
+            row_height,
+            padding,
+            size: (width, height),
+        })
+    }
+
+    /// Split `root` into the area for the chart and, if the legend goes outside the plot, the
+    /// area for the legend
+    #[allow(clippy::type_complexity)]
+    pub fn split<DB: DrawingBackend>(
+        &self,
+        root: &DrawingArea<DB, Shift>,
+        entries: &[LegendEntry<DB>],
+        theme: &Theme,
+    ) -> Result<(DrawingArea<DB, Shift>, Option<DrawingArea<DB, Shift>>), render::Error> {
+        if !self.show() || entries.is_empty() || !self.position().is_outside() {
+            return Ok((root.clone(), None));
+        }
+        let names: Vec<_> = self
+            .arrange(entries)
+            .iter()
+            .map(|e| e.name.as_str())
+            .collect();
+        let (w, h) = self.layout(&names, theme)?.size;
+        let (rw, rh) = root.dim_in_pixel();
+        let (chart, legend) = match self.position() {
+            LegendPosition::Below => root.split_vertically((rh as i32 - h - 2 * EDGE).max(0)),
+            _ => root.split_horizontally((rw as i32 - w - 2 * EDGE).max(0)),
+        };
+        Ok((chart, Some(legend)))
+    }
+
+    /// Draw the legend on `plot`, the plotting area of the chart, or on `outside` if it was split
+    /// off for the legend
+    pub fn draw<DB: DrawingBackend>(
+        &self,
+        plot: &DrawingArea<DB, Shift>,
+        outside: Option<&DrawingArea<DB, Shift>>,
+        entries: &[LegendEntry<DB>],
+        theme: &Theme,
+    ) -> Result<(), render::Error> {
+        if !self.show() || entries.is_empty() {
+            return Ok(());
+        }
+        let entries = self.arrange(entries);
+        let names: Vec<_> = entries.iter().map(|e| e.name.as_str()).collect();
+        let layout = self.layout(&names, theme)?;
+        let area = outside.unwrap_or(plot);
+        let (w, h) = layout.size;
+        let (aw, ah) = area.dim_in_pixel();
+        let (aw, ah) = (aw as i32, ah as i32);
+        use LegendPosition::*;
+        let x = match self.position() {
+            UpperLeft | MiddleLeft | LowerLeft | OutsideRight => EDGE,
+            UpperMiddle | MiddleMiddle | LowerMiddle | Below => (aw - w) / 2,
+            UpperRight | MiddleRight | LowerRight => aw - w - EDGE,
+        };
+        let y = match self.position() {
+            UpperLeft | UpperMiddle | UpperRight | Below => EDGE,
+            MiddleLeft | MiddleMiddle | MiddleRight | OutsideRight => (ah - h) / 2,
+            LowerLeft | LowerMiddle | LowerRight => ah - h - EDGE,
+        };
+        let rect = [(x, y), (x + w, y + h)];
+        area.draw(&Rectangle::new(rect, self.background(theme).filled()))?;
+        area.draw(&Rectangle::new(rect, self.border(theme).stroke_width(1)))?;
+
+        let font = self.font(theme);
+        let style = theme
+            .text_style(&font)
+            .pos(Pos::new(HPos::Left, VPos::Center));
+        let ncols = layout.columns.len();
+        for (n, entry) in entries.into_iter().enumerate() {
+            let (row, col) = ((n / ncols) as i32, n % ncols);
+            let left = x
+                + layout.padding
+                + layout.columns[..col].iter().sum::<i32>()
+                + col as i32 * layout.padding * 2;
+            let mid = y + layout.padding + row * layout.row_height + layout.row_height / 2;
+            area.draw(&(entry.glyph)((left + 5, mid)))?;
+            area.draw(&Text::new(
+                entry.name.clone(),
+                (left + GLYPH_WIDTH, mid),
+                style.clone(),
+            ))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_legend_spec() {
+        let legend = |yaml: &str| Legend::from(serde_yaml::from_str::<LegendSpec>(yaml).unwrap());
+        assert!(!legend("false").show());
+        assert!(legend("{}").show());
+        let l = legend("{position: outside-right, order: reverse}");
+        assert_eq!(l.position(), LegendPosition::OutsideRight);
+        assert_eq!(l.columns(3), 1);
+        let l = legend("position: below");
+        assert_eq!(l.columns(3), 3);
+        assert_eq!(legend("columns: 0").columns(3), 1);
+        assert_eq!(legend("columns: 5").columns(3), 3);
+    }
+}
@@ -0,0 +1,313 @@
+use plotters::element::{Drawable, PointCollection};
+use plotters::prelude::DrawingBackend;
+use plotters::style::{Color, RGBAColor, ShapeStyle};
+use plotters_backend::{BackendCoord, DrawingErrorKind};
+use serde::Deserialize;
+
+use crate::utils::BezWrapper;
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum NamedDash {
+    Solid,
+    Dashed,
+    Dotted,
+}
+
+/// Dash pattern of a line, either named or the lengths in pixels of alternating dashes and gaps
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+pub enum Dash {
+    Named(NamedDash),
+    Custom(Vec<f64>),
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Interpolation {
+    /// Straight from each point to the next
+    #[default]
+    Linear,
+    /// Jump to each point's value at the point before it
+    StepBefore,
+    /// Stay at each point's value until the next point
+    StepAfter,
+    /// Smooth curve through the points that never overshoots them
+    Monotone,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct LineStyle {
+    /// Width of the line in pixels, default: 1
+    width: Option<f64>,
+    /// default: solid
+    dash: Option<Dash>,
+    /// 0 is invisible and 1 is solid, default: 1
+    opacity: Option<f64>,
+    /// How the line gets from one point to the next, default: linear
+    interpolation: Option<Interpolation>,
+}
+
+/// How far in pixels curves can be from the straight lines they are drawn with
+const TOLERANCE: f64 = 0.1;
+
+/// Shortest dash or gap in pixels, so a line isn't cut into more pieces than can be seen
+const MIN_DASH: f64 = 0.5;
+
+impl LineStyle {
+    pub fn width(&self) -> u32 {
+        self.width.unwrap_or(1.0).round() as u32
+    }
+
+    fn opacity(&self) -> f64 {
+        self.opacity.unwrap_or(1.0).clamp(0.0, 1.0)
+    }
+
+    pub fn interpolation(&self) -> Interpolation {
+        self.interpolation.unwrap_or_default()
+    }
+
+    /// Lengths of the dashes and gaps, empty for a solid line. Like SVG, an odd number of
+    /// lengths is repeated so that dashes and gaps alternate
+    fn dashes(&self) -> Vec<f64> {
+        let w = self.width().max(1) as f64;
+        let dashes = match &self.dash {
+            None | Some(Dash::Named(NamedDash::Solid)) => vec![],
+            Some(Dash::Named(NamedDash::Dashed)) => vec![6.0 * w, 4.0 * w],
+            Some(Dash::Named(NamedDash::Dotted)) => vec![w, 2.0 * w],
+            Some(Dash::Custom(d)) => d.iter().map(|l| l.max(0.0)).collect(),
+        };
+        if dashes.iter().sum::<f64>() <= 0.0 {
+            return vec![];
+        }
+        let mut dashes: Vec<_> = dashes.into_iter().map(|l| l.max(MIN_DASH)).collect();
+        if dashes.len() % 2 == 1 {
+            dashes.extend_from_within(..);
+        }
+        dashes
+    }
+
+    pub fn shape_style(&self, c: RGBAColor) -> ShapeStyle {
+        c.mix(self.opacity()).stroke_width(self.width())
+    }
+
+    /// The line through `points`, in `c`
+    pub fn element<C>(&self, points: Vec<C>, c: RGBAColor) -> Line<C> {
+        Line {
+            points,
+            style: self.clone(),
+            colour: c,
+        }
+    }
+
+    /// Draw the line through `points`, which are in pixels
+    pub fn draw<DB: DrawingBackend>(
+        &self,
+        points: &[BackendCoord],
+        c: RGBAColor,
+        backend: &mut DB,
+    ) -> Result<(), DrawingErrorKind<DB::ErrorType>> {
+        let style = self.shape_style(c);
+        let points = self.interpolation().apply(points);
+        let dashes = self.dashes();
+        if dashes.is_empty() {
+            return backend.draw_path(points.iter().map(|p| to_coord(*p)), &style);
+        }
+        for dash in dashed(&points, &dashes) {
+            backend.draw_path(dash.into_iter().map(to_coord), &style)?;
+        }
+        Ok(())
+    }
+}
+
+impl Interpolation {
+    /// Points of straight lines following the interpolation between `points`, in pixels
+    pub fn apply(self, points: &[BackendCoord]) -> Vec<kurbo::Point> {
+        let points: Vec<_> = points
+            .iter()
+            .map(|(x, y)| kurbo::Point::new(*x as f64, *y as f64))
+            .collect();
+        let steps = |corner: fn(kurbo::Point, kurbo::Point) -> kurbo::Point| {
+            points
+                .iter()
+                .enumerate()
+                .flat_map(|(n, p)| match n {
+                    0 => vec![*p],
+                    _ => vec![corner(points[n - 1], *p), *p],
+                })
+                .collect()
+        };
+        match self {
+            Interpolation::Linear => points,
+            Interpolation::StepBefore => steps(|a, b| kurbo::Point::new(a.x, b.y)),
+            Interpolation::StepAfter => steps(|a, b| kurbo::Point::new(b.x, a.y)),
+            Interpolation::Monotone => BezWrapper::monotone(&points).flattened(TOLERANCE),
+        }
+    }
+}
+
+fn to_coord(p: kurbo::Point) -> BackendCoord {
+    (p.x.round() as i32, p.y.round() as i32)
+}
+
+/// Pieces of the line through `points` that are drawn, for dashes and gaps of lengths `dashes`
+fn dashed(points: &[kurbo::Point], dashes: &[f64]) -> Vec<Vec<kurbo::Point>> {
+    let mut pieces = Vec::new();
+    let mut current = points.first().map(|p| vec![*p]).unwrap_or_default();
+    // which length of `dashes` we are on and how much of it is left
+    let (mut n, mut left) = (0, dashes[0]);
+    for w in points.windows(2) {
+        let (mut from, to) = (w[0], w[1]);
+        let mut len = from.distance(to);
+        while len >= left {
+            let at = if len > 0.0 {
+                from.lerp(to, left / len)
+            } else {
+                from
+            };
+            if n % 2 == 0 {
+                current.push(at);
+                pieces.push(std::mem::take(&mut current));
+            } else {
+                current = vec![at];
+            }
+            len -= left;
+            from = at;
+            n = (n + 1) % dashes.len();
+            left = dashes[n];
+        }
+        left -= len;
+        if n % 2 == 0 {
+            current.push(to);
+        }
+    }
+    if n % 2 == 0 && current.len() > 1 {
+        pieces.push(current);
+    }
+    pieces
+}
+
+/// Line through a series of points, drawn in a [`LineStyle`]
+pub struct Line<C> {
+    points: Vec<C>,
+    style: LineStyle,
+    colour: RGBAColor,
+}
+
+impl<'a, C> PointCollection<'a, C> for &'a Line<C> {
+    type Point = &'a C;
+    type IntoIter = &'a [C];
+    fn point_iter(self) -> Self::IntoIter {
+        &self.points
+    }
+}
+
+impl<C, DB: DrawingBackend> Drawable<DB> for Line<C> {
+    fn draw<I: Iterator<Item = BackendCoord>>(
+        &self,
+        pos: I,
+        backend: &mut DB,
+        _: (u32, u32),
+    ) -> Result<(), DrawingErrorKind<DB::ErrorType>> {
+        let points: Vec<_> = pos.collect();
+        self.style.draw(&points, self.colour, backend)
+    }
+}
+
+/// Area between two lines following the same interpolation, `bottom` runs in the same
+/// direction as `top`
+pub struct Area<C> {
+    top: Vec<C>,
+    bottom: Vec<C>,
+    interpolation: Interpolation,
+    style: ShapeStyle,
+}
+
+impl<C> Area<C> {
+    pub fn new(
+        top: Vec<C>,
+        bottom: Vec<C>,
+        interpolation: Interpolation,
+        style: ShapeStyle,
+    ) -> Self {
+        Self {
+            top,
+            bottom,
+            interpolation,
+            style,
+        }
+    }
+}
+
+impl<'a, C> PointCollection<'a, C> for &'a Area<C> {
+    type Point = &'a C;
+    type IntoIter = std::iter::Chain<std::slice::Iter<'a, C>, std::slice::Iter<'a, C>>;
+    fn point_iter(self) -> Self::IntoIter {
+        self.top.iter().chain(self.bottom.iter())
+    }
+}
+
+impl<C, DB: DrawingBackend> Drawable<DB> for Area<C> {
+    fn draw<I: Iterator<Item = BackendCoord>>(
+        &self,
+        pos: I,
+        backend: &mut DB,
+        _: (u32, u32),
+    ) -> Result<(), DrawingErrorKind<DB::ErrorType>> {
+        let mut points: Vec<_> = pos.collect();
+        let bottom = points.split_off(self.top.len());
+        let mut outline = self.interpolation.apply(&points);
+        outline.extend(self.interpolation.apply(&bottom).into_iter().rev());
+        backend.fill_polygon(outline.into_iter().map(to_coord), &self.style)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dashed() {
+        let pts = [(0.0, 0.0), (10.0, 0.0), (10.0, 5.0)].map(kurbo::Point::from);
+        let pieces = dashed(&pts, &[4.0, 2.0]);
+        let xs: Vec<Vec<_>> = pieces
+            .iter()
+            .map(|p| p.iter().map(|p| (p.x, p.y)).collect())
+            .collect();
+        assert_eq!(
+            xs,
+            [
+                vec![(0.0, 0.0), (4.0, 0.0)],
+                vec![(6.0, 0.0), (10.0, 0.0)],
+                vec![(10.0, 2.0), (10.0, 5.0)],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dashes() {
+        let dashes = |d: &str| serde_yaml::from_str::<LineStyle>(d).unwrap().dashes();
+        assert_eq!(dashes("{dash: [3]}"), [3.0, 3.0]);
+        assert_eq!(dashes("{dash: [4, 1, 2]}"), [4.0, 1.0, 2.0, 4.0, 1.0, 2.0]);
+        assert_eq!(dashes("{dash: [1e-9, 0]}"), [MIN_DASH, MIN_DASH]);
+        assert!(dashes("{dash: [0, 0]}").is_empty());
+        assert_eq!(dashes("{dash: dashed, width: 2}"), [12.0, 8.0]);
+    }
+
+    #[test]
+    fn test_steps() {
+        let pts = [(0, 0), (10, 5), (20, 0)];
+        let after: Vec<_> = Interpolation::StepAfter
+            .apply(&pts)
+            .into_iter()
+            .map(to_coord)
+            .collect();
+        assert_eq!(after, [(0, 0), (10, 0), (10, 5), (20, 5), (20, 0)]);
+        let before: Vec<_> = Interpolation::StepBefore
+            .apply(&pts)
+            .into_iter()
+            .map(to_coord)
+            .collect();
+        assert_eq!(before, [(0, 0), (0, 5), (10, 5), (10, 0), (20, 0)]);
+    }
+}
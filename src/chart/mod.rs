@@ -1,17 +1,29 @@
+mod annotation;
+mod axis;
 mod charts;
+mod error_bar;
+mod figure;
+mod legend;
+mod line;
 mod render;
+mod scale;
+mod time;
+mod value_label;
 
-use plotters::prelude::{Cartesian2d, ChartBuilder, ChartContext, DrawingBackend, Ranged};
+use std::path::{Path, PathBuf};
+
+use plotters::prelude::{Cartesian2d, ChartBuilder, ChartContext, DrawingBackend};
+use plotters::style::RGBAColor;
 use serde::Deserialize;
 
 pub use charts::*;
-pub use render::*;
 
+use crate::palette::{Palette, PaletteIter};
 use crate::render::{CssColour, FontInfo};
+use crate::theme::{Theme, ThemeSpec};
 
-#[derive(Deserialize, Debug)]
-#[serde(remote = "plotters::style::RGBAColor")]
-struct RGBAColorDef(u8, u8, u8, f64);
+use self::annotation::{Annotation, AnnotationAxis};
+use self::legend::{Legend, LegendEntry, LegendSpec};
 
 #[derive(Clone, Debug, Deserialize)]
 pub struct DatasetMeta {
@@ -20,6 +32,16 @@ pub struct DatasetMeta {
     colour: Option<CssColour>,
 }
 
+/// Fonts of parts of the chart, any not given use the chart's font
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct Fonts {
+    caption: Option<FontInfo>,
+    /// Titles of the axes
+    labels: Option<FontInfo>,
+    /// Values and categories along the axes
+    ticks: Option<FontInfo>,
+}
+
 #[derive(Clone, Debug, Deserialize)]
 pub struct XY<T> {
     x: T,
@@ -46,22 +68,61 @@ impl From<XY<f64>> for kurbo::Point {
     }
 }
 
+/// A set of values along with their metadata, `O` is any options specific to the chart type
 #[derive(Clone, Debug, Deserialize)]
-pub struct Dataset<T: Clone> {
+pub struct Dataset<T: Clone, O: Clone = ()> {
     values: Vec<T>,
     #[serde(flatten)]
     extra: DatasetMeta,
+    #[serde(flatten)]
+    options: O,
 }
 #[derive(Clone, Debug, Deserialize)]
-pub struct ChartInfo<Pt: Clone> {
-    datasets: Vec<Dataset<Pt>>,
+pub struct ChartInfo<Pt: Clone, O: Clone = ()> {
+    datasets: Vec<Dataset<Pt, O>>,
     font: Option<FontInfo>,
+    /// Fonts of the caption, axis titles and ticks, default: `font`
+    fonts: Option<Fonts>,
+    /// TTF or OTF files relative to the spec, whose families can then be used by any font
+    font_files: Option<Vec<PathBuf>>,
     margins: Option<XY<Option<f64>>>,
     caption: Option<String>,
+    /// Lines, ranges and text drawn over the datasets
+    annotations: Option<Vec<Annotation>>,
+    /// Where and how to draw the legend, or just whether to. default: upper right
+    legend: Option<LegendSpec>,
+    /// Colours given to datasets without one, default: the theme's palette
+    palette: Option<Palette>,
+    /// Name of a built-in theme, path to a theme file or the theme itself. default: light
+    #[serde(rename = "theme")]
+    theme_spec: Option<ThemeSpec>,
+    /// Theme loaded from `theme_spec` or the command line
+    #[serde(skip)]
+    theme: Theme,
 }
-impl<Pt: Clone> ChartInfo<Pt> {
+impl<Pt: Clone, O: Clone> ChartInfo<Pt, O> {
     fn font(&self) -> FontInfo {
-        self.font.to_owned().unwrap_or_default()
+        self.font.to_owned().unwrap_or_else(|| self.theme.font())
+    }
+
+    fn fonts(&self) -> Fonts {
+        self.fonts.clone().unwrap_or_default()
+    }
+
+    fn caption_font(&self) -> FontInfo {
+        self.fonts().caption.unwrap_or_else(|| self.font())
+    }
+
+    fn label_font(&self) -> FontInfo {
+        self.fonts().labels.unwrap_or_else(|| self.font())
+    }
+
+    fn tick_font(&self) -> FontInfo {
+        self.fonts().ticks.unwrap_or_else(|| self.font())
+    }
+
+    fn theme(&self) -> &Theme {
+        &self.theme
     }
 
     fn margins(&self) -> XY<f64> {
@@ -82,22 +143,91 @@ impl<Pt: Clone> ChartInfo<Pt> {
     pub fn caption(&self) -> String {
         self.caption.to_owned().unwrap_or("".to_owned())
     }
+
+    fn annotations(&self) -> &[Annotation] {
+        self.annotations.as_deref().unwrap_or_default()
+    }
+
+    fn legend(&self) -> Legend {
+        self.legend
+            .clone()
+            .map(Legend::from)
+            .unwrap_or_default()
+            .or_font(self.font())
+    }
+
+    /// Colours of the chart's palette, or the theme's if it doesn't have one
+    fn colours(&self) -> PaletteIter {
+        match &self.palette {
+            Some(p) => p.iter(),
+            None => self.theme.colours(),
+        }
+    }
+
+    /// Colour of each dataset, those without one are given the next colour from the palette
+    fn dataset_colours(&self) -> Vec<RGBAColor> {
+        let mut citer = self.colours();
+        self.datasets
+            .iter()
+            .map(|dset| {
+                dset.extra
+                    .colour
+                    .map(|c| c.as_rgba())
+                    .unwrap_or_else(|| citer.next().unwrap())
+            })
+            .collect()
+    }
 }
 
 #[derive(Clone, Debug, Deserialize)]
-pub struct Chart<C, Pt: Clone> {
+pub struct Chart<C, Pt: Clone, O: Clone = ()> {
     #[serde(flatten)]
     extra: C,
     #[serde(flatten)]
-    info: ChartInfo<Pt>,
+    info: ChartInfo<Pt, O>,
+}
+impl<C, Pt: Clone, O: Clone> Chart<C, Pt, O> {
+    /// Use `theme` if given, otherwise load the chart's own theme, or use `parent` if it doesn't
+    /// have one. Theme files named in the chart are found relative to `dir`
+    pub fn load_theme(
+        &mut self,
+        theme: Option<&Theme>,
+        parent: &Theme,
+        dir: &Path,
+    ) -> anyhow::Result<&Theme> {
+        self.info.theme = match (theme, &self.info.theme_spec) {
+            (Some(t), _) => t.clone(),
+            (None, Some(spec)) => spec.load(dir)?,
+            (None, None) => parent.clone(),
+        };
+        Ok(&self.info.theme)
+    }
+
+    /// Theme of the chart, once it has been loaded with [`Chart::load_theme`]
+    pub fn theme(&self) -> &Theme {
+        self.info.theme()
+    }
+
+    /// Font files named in the chart, relative to the spec
+    pub fn font_files(&self) -> &[PathBuf] {
+        self.info.font_files.as_deref().unwrap_or_default()
+    }
 }
 pub trait ChartType: Clone {
     type DataPoint: Clone;
-    type X: Ranged;
-    type Y: Ranged;
+    /// Per-dataset options, `()` if there are none
+    type DatasetOptions: Clone;
+    type X: AnnotationAxis;
+    type Y: AnnotationAxis;
+    #[allow(clippy::type_complexity)]
     fn render_datasets<'a, 'b, DB: DrawingBackend>(
         &self,
-        info: &ChartInfo<Self::DataPoint>,
+        info: &ChartInfo<Self::DataPoint, Self::DatasetOptions>,
         c: &mut ChartBuilder<'a, 'b, DB>,
     ) -> Result<ChartContext<'a, DB, Cartesian2d<Self::X, Self::Y>>, crate::render::Error>;
+    /// Legend entry for each dataset, in the order they are given
+    fn legend_entries<'a, DB: DrawingBackend + 'a>(
+        &self,
+        info: &ChartInfo<Self::DataPoint, Self::DatasetOptions>,
+    ) -> Vec<LegendEntry<'a, DB>>;
 }
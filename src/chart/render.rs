@@ -1,44 +1,79 @@
-use plotters::style::full_palette::GREY;
-use plotters::style::{Color, FontDesc, FontFamily, FontStyle, WHITE};
+use plotters::coord::Shift;
+use plotters::prelude::DrawingArea;
 
-use crate::render::{self, Render};
+use crate::render::Render;
 
+use super::annotation::AnnotationAxis;
 use super::Chart;
 
 use super::*;
 type Result<T> = std::result::Result<T, crate::render::Error>;
 
-impl<C: ChartType> Render for Chart<C, C::DataPoint> {
+impl<C: ChartType> Render for Chart<C, C::DataPoint, C::DatasetOptions> {
     type Error = crate::render::Error;
-    fn render<DB: DrawingBackend>(&self, c: &mut ChartBuilder<DB>) -> Result<()> {
-        let margins = self.info.margins();
+    fn render<DB: DrawingBackend>(&self, root: &DrawingArea<DB, Shift>) -> Result<()> {
+        let legend = self.info.legend();
+        let entries = self.extra.legend_entries(&self.info);
+        let (area, outside) = legend.split(root, &entries, self.info.theme())?;
+        let caption_font = self.info.caption_font();
+        let mut c = ChartBuilder::on(&area);
+        self.info.apply_margins(&mut c);
+        self.info.apply_caption(&mut c, &caption_font);
+        let chart = self.extra.render_datasets(&self.info, &mut c)?;
+        self.info.draw_annotations(&chart)?;
+        legend.draw(
+            &chart.plotting_area().strip_coord_spec(),
+            outside.as_ref(),
+            &entries,
+            self.info.theme(),
+        )?;
+        Ok(())
+    }
+}
+
+impl<Pt: Clone, O: Clone> ChartInfo<Pt, O> {
+    pub(super) fn apply_margins<DB: DrawingBackend>(&self, c: &mut ChartBuilder<DB>) {
+        let margins = self.margins();
         c.margin_left(margins.x)
             .margin_right(margins.x)
             .margin_bottom(margins.y)
             .margin_top(margins.y);
-        let tfont = FontDesc::new(FontFamily::SansSerif, 12.0, FontStyle::Normal);
-        let chwidth = tfont
-            .box_size("  ")
-            .map_err(|e| render::Error::FontLoading(e.to_string()))?
-            .0;
-        let mut chart = self.extra.render_datasets(&self.info, c)?;
-        chart
-            .configure_series_labels()
-            .position(plotters::prelude::SeriesLabelPosition::UpperRight)
-            .label_font(tfont.clone())
-            .background_style(WHITE.mix(0.8))
-            .border_style(GREY.mix(0.6).stroke_width(1))
-            .margin(chwidth * 4)
-            .draw()?;
+    }
+
+    /// Caption the chart in `font`, which has to outlive the builder
+    pub(super) fn apply_caption<'b, DB: DrawingBackend>(
+        &self,
+        c: &mut ChartBuilder<'_, 'b, DB>,
+        font: &'b FontInfo,
+    ) {
+        c.caption(self.caption(), self.theme.text_style(font));
+    }
+
+    /// Draw the annotations over whatever is on `chart` so far
+    pub(super) fn draw_annotations<DB: DrawingBackend, X: AnnotationAxis, Y: AnnotationAxis>(
+        &self,
+        chart: &ChartContext<DB, Cartesian2d<X, Y>>,
+    ) -> Result<()> {
+        let font = self.font();
+        let text = self.theme.text_style(&font);
+        for a in self.annotations() {
+            a.draw(chart, &text)?;
+        }
         Ok(())
     }
 }
+
 impl Render for Charts {
     type Error = crate::render::Error;
-    fn render<DB: DrawingBackend>(&self, r: &mut ChartBuilder<DB>) -> Result<()> {
+    fn render<DB: DrawingBackend>(&self, r: &DrawingArea<DB, Shift>) -> Result<()> {
         match &self {
             Charts::XYScatter(c) => c.render(r),
             Charts::Bar(c) => c.render(r),
+            Charts::Box(c) => c.render(r),
+            Charts::Heatmap(c) => c.render(r),
+            Charts::Histogram(c) => c.render(r),
+            Charts::Pie(c) => c.render(r),
+            Charts::Figure(f) => f.render(r),
         }
     }
 }
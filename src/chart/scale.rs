@@ -0,0 +1,265 @@
+use std::ops::Range;
+
+use plotters::coord::{
+    ranged1d::{BoldPoints, KeyPointHint, NoDefaultFormatting, ValueFormatter},
+    types::RangedCoordf64,
+};
+use plotters::prelude::Ranged;
+use serde::Deserialize;
+
+use super::annotation::{AnnotationAxis, AxisValue};
+use super::axis::{AxisOptions, TickFormat, Ticks};
+use crate::render;
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Scale {
+    #[default]
+    Linear,
+    /// Base 10 logarithmic, only positive values can be shown
+    Log,
+    /// Linear around zero and logarithmic away from it, so zero and negative values can be shown
+    Symlog,
+}
+
+impl Scale {
+    /// Where `v` goes on the axis, which is linear in the result
+    pub(super) fn transform(self, v: f64) -> f64 {
+        match self {
+            Scale::Linear => v,
+            Scale::Log => v.log10(),
+            Scale::Symlog => v.signum() * v.abs().ln_1p() / std::f64::consts::LN_10,
+        }
+    }
+
+    pub(super) fn inverse(self, v: f64) -> f64 {
+        match self {
+            Scale::Linear => v,
+            Scale::Log => 10f64.powf(v),
+            Scale::Symlog => v.signum() * (10f64.powf(v.abs()) - 1.0),
+        }
+    }
+
+    /// Check `v` can be shown on this scale, `what` describes where it came from for the error
+    pub fn check(self, v: f64, what: impl FnOnce() -> String) -> Result<(), render::Error> {
+        if self == Scale::Log && v <= 0.0 {
+            Err(render::Error::InvalidChart(format!(
+                "{} is {} but a log scale can only show positive values",
+                what(),
+                v
+            )))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Range from the smallest to the largest of `values`. Widened if they're all the same (or
+/// there aren't any) so that it can still be drawn
+pub fn span(values: impl IntoIterator<Item = f64>) -> Range<f64> {
+    let (min, max) = values
+        .into_iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), v| {
+            (min.min(v), max.max(v))
+        });
+    if min < max {
+        min..max
+    } else if min == max && min != 0.0 {
+        // keeps the sign, so it still works on a log scale
+        let pad = min.abs() / 2.0;
+        min - pad..max + pad
+    } else {
+        0.0..1.0
+    }
+}
+
+/// Numeric axis with a linear, log or symlog scale
+pub struct ScaledAxis {
+    scale: Scale,
+    range: Range<f64>,
+    ticks: Option<Ticks>,
+    format: Option<TickFormat>,
+}
+
+impl ScaledAxis {
+    pub fn new(scale: Scale, range: Range<f64>) -> Self {
+        Self {
+            scale,
+            range,
+            ticks: None,
+            format: None,
+        }
+    }
+
+    /// Use the ticks and tick format from `opts`, the range is left alone
+    pub fn with_options(self, opts: &AxisOptions) -> Self {
+        Self {
+            ticks: opts.ticks().cloned(),
+            format: opts.tick_format().cloned(),
+            ..self
+        }
+    }
+
+    /// Powers of ten (and zero, for symlog) inside the range, thinned out to fit `max` points
+    fn decades(&self, max: usize) -> Vec<f64> {
+        let Range { start, end } = self.range;
+        let mut exps = Vec::new();
+        let mut push_decades = |low: f64, high: f64, sign: f64| {
+            let low = low.max(f64::MIN_POSITIVE).log10().ceil() as i32;
+            let high = high.log10().floor() as i32;
+            exps.extend((low..=high).map(|e| (sign, e)));
+        };
+        match self.scale {
+            Scale::Linear => return vec![],
+            Scale::Log => push_decades(start, end, 1.0),
+            Scale::Symlog => {
+                // +-1 are too close to 0 to be labelled separately
+                let nearest: f64 = if start <= 0.0 && end >= 0.0 {
+                    10.0
+                } else {
+                    1.0
+                };
+                if start < 0.0 {
+                    push_decades(nearest.max(-end), -start, -1.0);
+                }
+                if end > 0.0 {
+                    push_decades(nearest.max(start), end, 1.0);
+                }
+            }
+        }
+        let mut pts: Vec<_> = exps
+            .into_iter()
+            .map(|(sign, e)| sign * 10f64.powi(e))
+            .collect();
+        if self.scale == Scale::Symlog && start <= 0.0 && end >= 0.0 {
+            pts.push(0.0);
+        }
+        pts.sort_by(f64::total_cmp);
+        let step = pts.len().div_ceil(max.max(1));
+        pts.into_iter().step_by(step.max(1)).collect()
+    }
+
+    /// Plain numbers, or 10^n for powers of ten on the non-linear scales
+    fn default_format(&self, value: f64) -> String {
+        let exp = value.abs().log10().round();
+        if self.scale == Scale::Linear {
+            RangedCoordf64::format(&value)
+        } else if value == 0.0 {
+            "0".to_owned()
+        } else if 10f64.powf(exp) == value.abs() {
+            let sign = if value < 0.0 { "-" } else { "" };
+            format!("{}10^{}", sign, exp)
+        } else {
+            RangedCoordf64::format(&value)
+        }
+    }
+}
+
+impl ValueFormatter<f64> for ScaledAxis {
+    fn format_ext(&self, value: &f64) -> String {
+        let default = self.default_format(*value);
+        match &self.format {
+            Some(f) => f.format(*value, default),
+            None => default,
+        }
+    }
+}
+
+impl Ranged for ScaledAxis {
+    type FormatOption = NoDefaultFormatting;
+
+    type ValueType = f64;
+
+    fn map(&self, value: &f64, limit: (i32, i32)) -> i32 {
+        let low = self.scale.transform(self.range.start);
+        let high = self.scale.transform(self.range.end);
+        let frac = (self.scale.transform(*value) - low) / (high - low);
+        limit.0 + (frac * (limit.1 - limit.0) as f64).round() as i32
+    }
+
+    fn key_points<Hint: KeyPointHint>(&self, hint: Hint) -> Vec<f64> {
+        let light = hint.weight().allow_light_points();
+        let max = match &self.ticks {
+            Some(Ticks::At(at)) => {
+                return at
+                    .iter()
+                    .copied()
+                    .filter(|v| (self.range.start..=self.range.end).contains(v))
+                    .collect()
+            }
+            Some(Ticks::Count(n)) if !light => hint.max_num_points().min(*n),
+            _ => hint.max_num_points(),
+        };
+        let decades = self.decades(max);
+        if decades.len() < 2 {
+            // not enough of a range for the powers of ten to be useful
+            let linear = RangedCoordf64::from(self.range.clone());
+            return if light {
+                linear.key_points(hint)
+            } else {
+                linear.key_points(BoldPoints(max))
+            };
+        }
+        if !light || self.scale != Scale::Log {
+            return decades;
+        }
+        // minor lines at each multiple within a decade
+        let minor: Vec<_> = decades
+            .iter()
+            .flat_map(|d| (1..10).map(move |m| d * m as f64))
+            .filter(|v| (self.range.start..=self.range.end).contains(v))
+            .collect();
+        if minor.len() <= hint.max_num_points() {
+            minor
+        } else {
+            decades
+        }
+    }
+
+    fn range(&self) -> Range<f64> {
+        self.range.clone()
+    }
+}
+
+impl AnnotationAxis for ScaledAxis {
+    fn position(&self, v: &AxisValue) -> Option<f64> {
+        match v {
+            AxisValue::Number(v) => Some(*v),
+            AxisValue::Text(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_span() {
+        assert_eq!(span([-0.5, 0.25, 0.1]), -0.5..0.25);
+        assert_eq!(span([0.0, 0.0]), 0.0..1.0);
+        assert_eq!(span([]), 0.0..1.0);
+        assert_eq!(span([2.0]), 1.0..3.0);
+        assert_eq!(span([-2.0]), -3.0..-1.0);
+    }
+
+    #[test]
+    fn test_log_key_points() {
+        let axis = ScaledAxis::new(Scale::Log, 0.5..2000.0);
+        assert_eq!(axis.decades(10), vec![1.0, 10.0, 100.0, 1000.0]);
+        assert_eq!(axis.decades(2), vec![1.0, 100.0]);
+        assert_eq!(axis.format_ext(&100.0), "10^2");
+        assert_eq!(axis.format_ext(&0.5), "0.5");
+
+        let axis = ScaledAxis::new(Scale::Log, 1.0..1000.0);
+        let pixels: Vec<_> = [1.0, 10.0, 100.0, 1000.0]
+            .iter()
+            .map(|v| axis.map(v, (0, 300)))
+            .collect();
+        assert_eq!(pixels, vec![0, 100, 200, 300]);
+
+        let sym = ScaledAxis::new(Scale::Symlog, -100.0..10.0);
+        assert_eq!(sym.decades(10), vec![-100.0, -10.0, 0.0, 10.0]);
+        assert_eq!(sym.format_ext(&-10.0), "-10^1");
+    }
+}
@@ -0,0 +1,182 @@
+use std::ops::Range;
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use plotters::coord::{
+    ranged1d::{BoldPoints, KeyPointHint, NoDefaultFormatting, ValueFormatter},
+    types::RangedDateTime,
+};
+use plotters::prelude::Ranged;
+
+use super::{
+    annotation::{AnnotationAxis, AxisValue},
+    axis::Ticks,
+    scale,
+};
+use crate::render;
+
+/// Parse an ISO-8601 date or date/time into seconds since the unix epoch. Anything without an
+/// offset is taken to be UTC
+pub fn parse_datetime(s: &str) -> Option<f64> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Some(to_secs(dt.naive_utc()));
+    }
+    [
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y-%m-%d %H:%M:%S%.f",
+        "%Y-%m-%dT%H:%M",
+    ]
+    .iter()
+    .find_map(|fmt| NaiveDateTime::parse_from_str(s, fmt).ok())
+    .or_else(|| {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d")
+            .ok()
+            .and_then(|d| d.and_hms_opt(0, 0, 0))
+    })
+    .map(to_secs)
+}
+
+/// Range from the earliest to the latest of `values`, a single instant is widened to the day
+/// around it
+pub fn span(values: impl IntoIterator<Item = f64>) -> Range<f64> {
+    const HALF_DAY: f64 = 60.0 * 60.0 * 12.0;
+    let values: Vec<_> = values.into_iter().collect();
+    match values.first() {
+        Some(v) if values.iter().all(|x| x == v) => v - HALF_DAY..v + HALF_DAY,
+        _ => scale::span(values),
+    }
+}
+
+fn to_secs(dt: NaiveDateTime) -> f64 {
+    dt.timestamp() as f64 + dt.timestamp_subsec_nanos() as f64 / 1e9
+}
+
+fn to_datetime(secs: f64) -> NaiveDateTime {
+    let nanos = ((secs - secs.floor()) * 1e9).round() as u32;
+    NaiveDateTime::from_timestamp_opt(secs.floor() as i64, nanos.min(999_999_999))
+        .unwrap_or_default()
+}
+
+/// Date/time axis with values in seconds since the unix epoch
+pub struct TimeAxis {
+    range: Range<f64>,
+    inner: RangedDateTime<NaiveDateTime>,
+    /// strftime format for the labels, picked from the range if not given
+    format: Option<String>,
+    ticks: Option<Ticks>,
+}
+
+impl TimeAxis {
+    /// Errors if `format` isn't a valid strftime format
+    pub fn new(range: Range<f64>, format: Option<String>) -> Result<Self, render::Error> {
+        if let Some(f) = &format {
+            if StrftimeItems::new(f).any(|i| matches!(i, Item::Error)) {
+                return Err(render::Error::InvalidChart(format!(
+                    "x_format '{}' is not a valid strftime format",
+                    f
+                )));
+            }
+        }
+        let inner = (to_datetime(range.start)..to_datetime(range.end)).into();
+        Ok(Self {
+            range,
+            inner,
+            format,
+            ticks: None,
+        })
+    }
+
+    /// Put the ticks here instead of where plotters picks, values are unix timestamps
+    pub fn with_ticks(self, ticks: Option<&Ticks>) -> Self {
+        Self {
+            ticks: ticks.cloned(),
+            ..self
+        }
+    }
+
+    fn format(&self) -> &str {
+        const DAY: f64 = 60.0 * 60.0 * 24.0;
+        if let Some(f) = &self.format {
+            return f;
+        }
+        // precise enough that the ticks plotters picks for the span don't get the same label
+        let span = self.range.end - self.range.start;
+        if span > DAY * 365.0 * 10.0 {
+            "%Y"
+        } else if span > DAY * 365.0 {
+            "%Y-%m"
+        } else if span > DAY * 3.0 {
+            "%Y-%m-%d"
+        } else if span > DAY {
+            "%m-%d %H:%M"
+        } else if span > 60.0 * 10.0 {
+            "%H:%M"
+        } else {
+            "%H:%M:%S"
+        }
+    }
+}
+
+impl ValueFormatter<f64> for TimeAxis {
+    fn format_ext(&self, value: &f64) -> String {
+        to_datetime(*value).format(self.format()).to_string()
+    }
+}
+
+impl Ranged for TimeAxis {
+    type FormatOption = NoDefaultFormatting;
+
+    type ValueType = f64;
+
+    fn map(&self, value: &f64, limit: (i32, i32)) -> i32 {
+        self.inner.map(&to_datetime(*value), limit)
+    }
+
+    fn key_points<Hint: KeyPointHint>(&self, hint: Hint) -> Vec<f64> {
+        let points = match &self.ticks {
+            Some(Ticks::At(at)) => {
+                return at
+                    .iter()
+                    .copied()
+                    .filter(|v| (self.range.start..=self.range.end).contains(v))
+                    .collect()
+            }
+            Some(Ticks::Count(n)) if !hint.weight().allow_light_points() => self
+                .inner
+                .key_points(BoldPoints(hint.max_num_points().min(*n))),
+            _ => self.inner.key_points(hint),
+        };
+        points.into_iter().map(to_secs).collect()
+    }
+
+    fn range(&self) -> Range<f64> {
+        self.range.clone()
+    }
+}
+
+impl AnnotationAxis for TimeAxis {
+    fn position(&self, v: &AxisValue) -> Option<f64> {
+        match v {
+            AxisValue::Number(v) => Some(*v),
+            AxisValue::Text(s) => parse_datetime(s),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_datetime() {
+        assert_eq!(parse_datetime("1970-01-02"), Some(86400.0));
+        assert_eq!(parse_datetime("1970-01-01T00:01:00"), Some(60.0));
+        assert_eq!(parse_datetime("1970-01-01 00:00:01.5"), Some(1.5));
+        assert_eq!(parse_datetime("1970-01-01T01:00:00+01:00"), Some(0.0));
+        assert_eq!(parse_datetime("yesterday"), None);
+
+        let axis = TimeAxis::new(0.0..86400.0 * 365.0, Some("%Y-%m".to_owned())).unwrap();
+        assert_eq!(axis.format_ext(&(86400.0 * 40.0)), "1970-02");
+        assert!(TimeAxis::new(0.0..1.0, Some("%Q".to_owned())).is_err());
+    }
+}
@@ -0,0 +1,121 @@
+use plotters::coord::Shift;
+use plotters::prelude::{DrawingArea, DrawingBackend, Text};
+use plotters::style::{Color, RGBAColor, TextStyle};
+use plotters_backend::BackendCoord;
+use serde::Deserialize;
+
+use super::axis::TickFormat;
+use crate::palette::contrasting;
+use crate::render::{self, CssColour, FontInfo};
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LabelPosition {
+    /// Inside the end of the bar, or below the point
+    Inside,
+    /// Past the end of the bar, or above the point
+    Outside,
+    /// Centred on the end of the bar, or on the point
+    End,
+}
+
+/// Value printed on each bar or point
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct ValueLabels {
+    /// Print the labels? default: true
+    show: Option<bool>,
+    /// How to write the values, default: as given
+    format: Option<TickFormat>,
+    /// default: inside for stacked bars, otherwise outside
+    position: Option<LabelPosition>,
+    /// default: the chart's font
+    font: Option<FontInfo>,
+    /// default: the theme's text colour, or black or white to stand out on the bar when inside it
+    #[serde(alias = "color")]
+    colour: Option<CssColour>,
+}
+
+/// Gap in pixels between a label and what it labels
+const GAP: f64 = 4.0;
+
+impl ValueLabels {
+    pub fn show(&self) -> bool {
+        self.show.unwrap_or(true)
+    }
+
+    pub fn text(&self, v: f64) -> String {
+        let default = v.to_string();
+        match &self.format {
+            Some(f) => f.format(v, default),
+            None => default,
+        }
+    }
+
+    /// Draw the label for `v` on `area` next to `at`, an absolute pixel position. `dir` is the
+    /// direction the bar grows in pixels (or up, for points) and `bg` the colour of the bar. `tfont`
+    /// is the chart's text style. The label is moved to keep it inside the area
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw<DB: DrawingBackend>(
+        &self,
+        area: &DrawingArea<DB, Shift>,
+        at: BackendCoord,
+        dir: (f64, f64),
+        v: f64,
+        tfont: &TextStyle,
+        default_position: LabelPosition,
+        bg: Option<RGBAColor>,
+    ) -> Result<(), render::Error> {
+        let text = self.text(v);
+        let position = self.position.unwrap_or(default_position);
+        let colour = match (self.colour, bg) {
+            (Some(c), _) => c.as_rgba(),
+            (None, Some(bg)) if position != LabelPosition::Outside => contrasting(bg).to_rgba(),
+            _ => render::text_colour(tfont),
+        };
+        let style = match &self.font {
+            Some(font) => font.to_text_style(),
+            None => tfont.clone(),
+        };
+        let (w, h) = style
+            .font
+            .box_size(&text)
+            .map_err(|e| render::Error::FontLoading(e.to_string()))?;
+        let (w, h) = (w as f64, h as f64);
+        // how far the centre of the label is from `at`, so its edge is GAP away
+        let along = GAP + dir.0.abs() * w / 2.0 + dir.1.abs() * h / 2.0;
+        let along = match position {
+            LabelPosition::Inside => -along,
+            LabelPosition::Outside => along,
+            LabelPosition::End => 0.0,
+        };
+        let base = area.get_base_pixel();
+        let (aw, ah) = area.dim_in_pixel();
+        let place = |at: i32, base: i32, dir: f64, size: f64, max: u32| {
+            let left = (at - base) as f64 + dir * along - size / 2.0;
+            left.min(max as f64 - size).max(0.0).round() as i32
+        };
+        let pos = (
+            place(at.0, base.0, dir.0, w, aw),
+            place(at.1, base.1, dir.1, h, ah),
+        );
+        area.draw(&Text::new(text, pos, style.color(&colour)))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_label_text() {
+        let labels = ValueLabels::default();
+        assert!(labels.show());
+        assert_eq!(labels.text(3.0), "3");
+        assert_eq!(labels.text(2.5), "2.5");
+        let labels: ValueLabels =
+            serde_yaml::from_str("{format: {precision: 1, suffix: '%'}, position: end}").unwrap();
+        assert_eq!(labels.text(3.0), "3.0%");
+        assert_eq!(labels.position, Some(LabelPosition::End));
+    }
+}
@@ -0,0 +1,83 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use tempfile::TempDir;
+
+/// Font files made available to fontconfig, which both the bitmap and cairo backends find fonts
+/// through. The files are copied into a new temporary directory, only readable by this user,
+/// which fontconfig is pointed at along with the system configuration. The directory is removed
+/// on drop.
+///
+/// Has to be loaded before any text is drawn or measured, as fontconfig only reads its
+/// configuration once
+pub struct FontFiles {
+    dir: TempDir,
+}
+
+/// Used by fontconfig when `FONTCONFIG_FILE` isn't set
+const SYSTEM_CONFIG: &str = "/etc/fonts/fonts.conf";
+
+impl FontFiles {
+    pub fn load(files: &[PathBuf]) -> Result<Self> {
+        let dir = tempfile::Builder::new()
+            .prefix("charlt-fonts-")
+            .tempdir()
+            .map_err(|e| anyhow!("failed to create a directory for font files: {}", e))?;
+        let fonts = Self { dir };
+        for (n, f) in files.iter().enumerate() {
+            let ext = f.extension().and_then(|e| e.to_str()).unwrap_or_default();
+            if !matches!(ext.to_lowercase().as_str(), "ttf" | "otf") {
+                return Err(anyhow!(
+                    "font file {} is not a ttf or otf file",
+                    f.display()
+                ));
+            }
+            // numbered so files with the same name from different directories don't clash
+            std::fs::copy(f, fonts.dir.path().join(format!("{}.{}", n, ext)))
+                .map_err(|e| anyhow!("failed to read font file {}: {}", f.display(), e))?;
+        }
+        let system = std::env::var_os("FONTCONFIG_FILE")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| SYSTEM_CONFIG.into());
+        let conf = fonts.dir.path().join("fonts.conf");
+        std::fs::write(&conf, config(fonts.dir.path(), &system))?;
+        std::env::set_var("FONTCONFIG_FILE", &conf);
+        Ok(fonts)
+    }
+}
+
+/// Configuration adding the fonts in `dir` to those from `system`
+fn config(dir: &Path, system: &Path) -> String {
+    let escape = |p: &Path| {
+        p.display()
+            .to_string()
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+    };
+    format!(
+        r#"<?xml version="1.0"?>
+<!DOCTYPE fontconfig SYSTEM "urn:fontconfig:fonts.dtd">
+<fontconfig>
+  <cachedir>{dir}</cachedir>
+  <include ignore_missing="yes">{system}</include>
+  <dir>{dir}</dir>
+</fontconfig>
+"#,
+        dir = escape(dir),
+        system = escape(system),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config() {
+        let conf = config(Path::new("/tmp/a&b"), Path::new(SYSTEM_CONFIG));
+        assert!(conf.contains("<dir>/tmp/a&amp;b</dir>"));
+        assert!(conf.contains(">/etc/fonts/fonts.conf</include>"));
+        assert!(FontFiles::load(&["font.woff".into()]).is_err());
+    }
+}
@@ -2,22 +2,29 @@ use std::fs::File;
 
 use anyhow::{anyhow, Result};
 use api::InputFormat;
+use chart::Charts;
 use clap::{builder::PossibleValue, Parser, ValueEnum};
+use font_files::FontFiles;
 use plotters::{
     coord::Shift,
-    prelude::{BitMapBackend, ChartBuilder, DrawingArea, DrawingBackend, IntoDrawingArea},
+    prelude::{BitMapBackend, DrawingArea, DrawingBackend, IntoDrawingArea},
+    style::RGBAColor,
 };
 use render::Render;
 use serde::{Deserialize, Serialize};
+use theme::{Theme, ThemeSpec};
 
 mod api;
 mod chart;
+mod font_files;
 mod palette;
 mod render;
 mod serde_lua;
+mod theme;
 mod utils;
 
-use std::path::Path;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 enum OutputFormat {
@@ -46,19 +53,30 @@ impl TryFrom<&Path> for OutputFormat {
         let exten = value.extension().ok_or(())?;
         for fmt in Self::value_variants() {
             if fmt.extension() == exten {
-                return Ok(fmt.clone());
+                return Ok(*fmt);
             }
         }
         Err(())
     }
 }
 
+/// Path meaning stdin for the input and stdout for the output
+const STDIO: &str = "-";
+
+fn is_stdio(path: &Path) -> bool {
+    path == Path::new(STDIO)
+}
+
 #[derive(Parser, Debug)]
 struct CliArgs {
-    #[arg(name = "INPUT")]
+    #[arg(name = "INPUT", help = "spec to render, - for stdin")]
     input: std::path::PathBuf,
 
-    #[arg(name = "OUTPUT", short = 'o')]
+    #[arg(
+        name = "OUTPUT",
+        short = 'o',
+        help = "where to write the chart, - for stdout"
+    )]
     output: std::path::PathBuf,
 
     #[arg(long, default_value_t = 600, help = "width of chart")]
@@ -80,6 +98,12 @@ struct CliArgs {
         help = "input format to use, if not provided deduced from extension"
     )]
     input_format: Option<InputFormat>,
+
+    #[arg(
+        long,
+        help = "theme to use instead of the chart's: light, dark, print, high-contrast or the path to a theme file"
+    )]
+    theme: Option<String>,
 }
 impl ValueEnum for OutputFormat {
     fn value_variants<'a>() -> &'a [Self] {
@@ -94,46 +118,183 @@ impl ValueEnum for OutputFormat {
         })
     }
 }
-fn do_render<DB: DrawingBackend>(args: &CliArgs, root: DrawingArea<DB, Shift>) -> Result<()> {
-    let mut builder = ChartBuilder::on(&root);
-    let chart = api::load_chart(
-        &mut File::open(&args.input)?,
-        args.input_format
-            .or_else(|| InputFormat::from_path(args.input.as_ref()))
-            .ok_or(anyhow!("unknown input format"))?,
-    )?;
-    root.fill(&plotters::style::WHITE)
+/// A chart to render on its own page, with the background of its theme
+struct Page {
+    chart: Charts,
+    background: RGBAColor,
+}
+
+/// Load the charts in the input with their themes and fonts
+fn load_pages(args: &CliArgs) -> Result<(Vec<Page>, Option<FontFiles>)> {
+    let format = args
+        .input_format
+        .or_else(|| InputFormat::from_path(args.input.as_ref()))
+        .ok_or(anyhow!("unknown input format, give it with --from"))?;
+    let mut input: Box<dyn Read> = if is_stdio(&args.input) {
+        Box::new(std::io::stdin().lock())
+    } else {
+        Box::new(File::open(&args.input)?)
+    };
+    let charts = api::load_charts(&mut input, format)?;
+    let theme = args
+        .theme
+        .as_ref()
+        .map(|t| ThemeSpec::Named(t.clone()).load(Path::new(".")))
+        .transpose()?;
+    let spec_dir = args.input.parent().unwrap_or(Path::new("."));
+    let font_files: Vec<_> = charts
+        .iter()
+        .flat_map(|c| c.font_files())
+        .map(|f| spec_dir.join(f))
+        .collect();
+    let fonts = (!font_files.is_empty())
+        .then(|| FontFiles::load(&font_files))
+        .transpose()?;
+    let pages = charts
+        .into_iter()
+        .map(|mut chart| {
+            let background = chart
+                .load_theme(theme.as_ref(), &Theme::default(), spec_dir)?
+                .background();
+            Ok(Page { chart, background })
+        })
+        .collect::<Result<_>>()?;
+    Ok((pages, fonts))
+}
+
+fn do_render<DB: DrawingBackend>(page: &Page, root: DrawingArea<DB, Shift>) -> Result<()> {
+    root.fill(&page.background)
         .map_err(|e| anyhow!(e.to_string()))?;
-    chart.render(&mut builder)?;
+    page.chart.render(&root)?;
     root.present().map_err(|e| anyhow!(e.to_string()))?;
     Ok(())
 }
 
+fn render_svg(page: &Page, size: (u32, u32)) -> Result<Vec<u8>> {
+    let mut svg = String::new();
+    do_render(
+        page,
+        plotters::backend::SVGBackend::with_string(&mut svg, size).into_drawing_area(),
+    )?;
+    Ok(svg.into_bytes())
+}
+
+fn render_png(page: &Page, (w, h): (u32, u32)) -> Result<Vec<u8>> {
+    let mut pixels = vec![0; w as usize * h as usize * 3];
+    do_render(
+        page,
+        BitMapBackend::with_buffer(&mut pixels, (w, h)).into_drawing_area(),
+    )?;
+    let mut png = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png, w, h);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&pixels)?;
+    writer.finish()?;
+    Ok(png)
+}
+
+/// Every page in one pdf
+fn render_pdf(pages: &[Page], (w, h): (u32, u32)) -> Result<Vec<u8>> {
+    let surface = cairo::PdfSurface::for_stream(w as f64, h as f64, Vec::<u8>::new())?;
+    let ctx = cairo::Context::new(&surface)?;
+    for page in pages {
+        do_render(
+            page,
+            plotters_cairo::CairoBackend::new(&ctx, (w, h))?.into_drawing_area(),
+        )?;
+        ctx.show_page()?;
+    }
+    let pdf = surface.finish_output_stream().map_err(|e| e.error)?;
+    Ok(*pdf
+        .downcast::<Vec<u8>>()
+        .expect("the pdf is written to a Vec"))
+}
+
+/// Write to the file at `path`, or stdout if it is `-`
+fn write_output(path: &Path, bytes: &[u8]) -> Result<()> {
+    if is_stdio(path) {
+        let mut out = std::io::stdout().lock();
+        out.write_all(bytes)?;
+        out.flush()?;
+    } else {
+        std::fs::write(path, bytes)
+            .map_err(|e| anyhow!("failed to write {}: {}", path.display(), e))?;
+    }
+    Ok(())
+}
+
+/// `output` numbered with the page, e.g. `out.svg` becomes `out-1.svg` for the first page
+fn page_path(output: &Path, page: usize) -> PathBuf {
+    let mut name = output.file_stem().unwrap_or_default().to_owned();
+    name.push(format!("-{}", page));
+    if let Some(ext) = output.extension() {
+        name.push(".");
+        name.push(ext);
+    }
+    output.with_file_name(name)
+}
+
 fn main() -> Result<()> {
     let args = CliArgs::parse();
 
     let size = (args.width, args.height);
-    match args
+    let format = args
         .output_format
         .or_else(|| args.output.as_path().try_into().ok())
-        .expect("unknown output format")
-    {
-        OutputFormat::Svg => do_render(
-            &args,
-            plotters::backend::SVGBackend::new(&args.output, size).into_drawing_area(),
-        ),
-        OutputFormat::Png => do_render(
-            &args,
-            BitMapBackend::new(&args.output, size).into_drawing_area(),
-        ),
-        OutputFormat::Pdf => {
-            let surface =
-                cairo::PdfSurface::new(args.width as f64, args.height as f64, &args.output)?;
-            do_render(
-                &args,
-                plotters_cairo::CairoBackend::new(&cairo::Context::new(&surface)?, size)?
-                    .into_drawing_area(),
-            )
-        }
+        .ok_or(anyhow!("unknown output format, give it with --to"))?;
+    let (pages, _fonts) = load_pages(&args)?;
+    if let OutputFormat::Pdf = format {
+        return write_output(&args.output, &render_pdf(&pages, size)?);
+    }
+    if pages.len() > 1 && is_stdio(&args.output) {
+        return Err(anyhow!(
+            "several charts can only be written to stdout as a pdf"
+        ));
+    }
+    for (n, page) in pages.iter().enumerate() {
+        let output = match pages.len() {
+            1 => args.output.clone(),
+            _ => page_path(&args.output, n + 1),
+        };
+        let bytes = match format {
+            OutputFormat::Svg => render_svg(page, size)?,
+            OutputFormat::Png => render_png(page, size)?,
+            OutputFormat::Pdf => unreachable!("pdf pages are all in one file"),
+        };
+        write_output(&output, &bytes)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_path() {
+        assert_eq!(
+            page_path(Path::new("a/out.svg"), 2),
+            Path::new("a/out-2.svg")
+        );
+        assert_eq!(page_path(Path::new("out"), 1), Path::new("out-1"));
+    }
+
+    #[test]
+    fn test_render_buffers() {
+        let spec = "{type: pie, categories: [a], datasets: [{name: a, values: [1]}]}";
+        let pages: Vec<_> = api::load_charts(&mut spec.as_bytes(), InputFormat::Yaml)
+            .unwrap()
+            .into_iter()
+            .map(|chart| Page {
+                chart,
+                background: RGBAColor(255, 255, 255, 1.0),
+            })
+            .collect();
+        let size = (60, 40);
+        assert!(render_svg(&pages[0], size).unwrap().starts_with(b"<svg"));
+        // png isn't checked as plotters' bitmap fill panics on misaligned reads in debug builds
+        assert!(render_pdf(&pages, size).unwrap().starts_with(b"%PDF"));
     }
 }
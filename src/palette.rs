@@ -1,313 +1,290 @@
-use plotters::style::Color;
+use plotters::style::{RGBAColor, RGBColor, BLACK, WHITE};
+use serde::Deserialize;
 
-pub fn colour_at(index: usize) -> &'static dyn Color {
-    PALETTE[index % PALETTE.len()]
+use crate::render::CssColour;
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum NamedPalette {
+    /// Wong's colour blind safe palette, without its black
+    #[default]
+    OkabeIto,
+    Tableau10,
+    /// ColorBrewer's qualitative sets
+    Set1,
+    Set2,
+    Dark2,
+    Paired,
+    /// Evenly spaced samples of the viridis scale, dark to light
+    Viridis,
+}
+
+impl NamedPalette {
+    fn colours(&self) -> &'static [(u8, u8, u8)] {
+        match self {
+            NamedPalette::OkabeIto => &OKABE_ITO,
+            NamedPalette::Tableau10 => &TABLEAU_10,
+            NamedPalette::Set1 => &SET1,
+            NamedPalette::Set2 => &SET2,
+            NamedPalette::Dark2 => &DARK2,
+            NamedPalette::Paired => &PAIRED,
+            NamedPalette::Viridis => &VIRIDIS,
+        }
+    }
+}
+
+/// Colours given to datasets in turn, either a named palette or a list of css colours
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+pub enum Palette {
+    Named(NamedPalette),
+    Colours(Vec<CssColour>),
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self::Named(NamedPalette::default())
+    }
+}
+
+impl Palette {
+    /// Colours of the palette in order, repeating forever. An empty list falls back to the
+    /// default palette
+    pub fn iter(&self) -> PaletteIter {
+        let colours: Vec<_> = match self {
+            Palette::Colours(cs) if !cs.is_empty() => cs.iter().map(|c| c.as_rgba()).collect(),
+            Palette::Colours(_) => return Palette::default().iter(),
+            Palette::Named(n) => n
+                .colours()
+                .iter()
+                .map(|(r, g, b)| RGBAColor(*r, *g, *b, 1.0))
+                .collect(),
+        };
+        PaletteIter { colours, i: 0 }
+    }
 }
 
 pub struct PaletteIter {
+    colours: Vec<RGBAColor>,
     i: usize,
 }
 
 impl Iterator for PaletteIter {
-    type Item = &'static dyn Color;
+    type Item = RGBAColor;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let c = colour_at(self.i);
-        self.i += 15;
+        let c = self.colours[self.i % self.colours.len()];
+        self.i += 1;
         Some(c)
     }
 }
 
-pub fn colours() -> PaletteIter {
-    PaletteIter { i: 0 }
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum NamedScale {
+    Viridis,
+    Magma,
+    Plasma,
+}
+
+impl NamedScale {
+    fn stops(&self) -> &'static [(u8, u8, u8)] {
+        match self {
+            NamedScale::Viridis => &VIRIDIS,
+            NamedScale::Magma => &MAGMA,
+            NamedScale::Plasma => &PLASMA,
+        }
+    }
+}
+
+/// Continuous colour scale, mapping 0..=1 onto a gradient
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+pub enum ColourScale {
+    Named(NamedScale),
+    /// Evenly spaced css colour stops
+    Stops(Vec<CssColour>),
+}
+
+impl Default for ColourScale {
+    fn default() -> Self {
+        Self::Named(NamedScale::Viridis)
+    }
+}
+
+impl ColourScale {
+    /// Colour `t` of the way along the scale, `t` is clamped to 0..=1
+    pub fn at(&self, t: f64) -> RGBAColor {
+        let stops: Vec<RGBAColor> = match self {
+            ColourScale::Named(n) => n
+                .stops()
+                .iter()
+                .map(|(r, g, b)| RGBAColor(*r, *g, *b, 1.0))
+                .collect(),
+            ColourScale::Stops(s) => s.iter().map(|c| c.as_rgba()).collect(),
+        };
+        match stops.as_slice() {
+            [] => RGBAColor(0, 0, 0, 1.0),
+            [c] => *c,
+            _ => {
+                let pos = t.clamp(0.0, 1.0) * (stops.len() - 1) as f64;
+                let n = (pos.floor() as usize).min(stops.len() - 2);
+                lerp(stops[n], stops[n + 1], pos - n as f64)
+            }
+        }
+    }
+}
+
+/// Black or white, whichever is more readable on `bg`
+pub fn contrasting(bg: RGBAColor) -> &'static RGBColor {
+    let luma = 0.299 * bg.0 as f64 + 0.587 * bg.1 as f64 + 0.114 * bg.2 as f64;
+    if luma > 140.0 {
+        &BLACK
+    } else {
+        &WHITE
+    }
+}
+
+fn lerp(a: RGBAColor, b: RGBAColor, t: f64) -> RGBAColor {
+    let ch = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t).round() as u8;
+    RGBAColor(
+        ch(a.0, b.0),
+        ch(a.1, b.1),
+        ch(a.2, b.2),
+        a.3 + (b.3 - a.3) * t,
+    )
 }
 
-const PALETTE: [&dyn Color; 287] = [
-    &plotters::style::colors::full_palette::BLACK,
-    &plotters::style::colors::full_palette::BLUE,
-    &plotters::style::colors::full_palette::BLUEGREY,
-    &plotters::style::colors::full_palette::BLUEGREY_50,
-    &plotters::style::colors::full_palette::BLUEGREY_100,
-    &plotters::style::colors::full_palette::BLUEGREY_200,
-    &plotters::style::colors::full_palette::BLUEGREY_300,
-    &plotters::style::colors::full_palette::BLUEGREY_400,
-    &plotters::style::colors::full_palette::BLUEGREY_500,
-    &plotters::style::colors::full_palette::BLUEGREY_600,
-    &plotters::style::colors::full_palette::BLUEGREY_700,
-    &plotters::style::colors::full_palette::BLUEGREY_800,
-    &plotters::style::colors::full_palette::BLUEGREY_900,
-    &plotters::style::colors::full_palette::BLUEGREY_A100,
-    &plotters::style::colors::full_palette::BLUEGREY_A200,
-    &plotters::style::colors::full_palette::BLUEGREY_A400,
-    &plotters::style::colors::full_palette::BLUEGREY_A700,
-    &plotters::style::colors::full_palette::BLUE_50,
-    &plotters::style::colors::full_palette::BLUE_100,
-    &plotters::style::colors::full_palette::BLUE_200,
-    &plotters::style::colors::full_palette::BLUE_300,
-    &plotters::style::colors::full_palette::BLUE_400,
-    &plotters::style::colors::full_palette::BLUE_500,
-    &plotters::style::colors::full_palette::BLUE_600,
-    &plotters::style::colors::full_palette::BLUE_700,
-    &plotters::style::colors::full_palette::BLUE_800,
-    &plotters::style::colors::full_palette::BLUE_900,
-    &plotters::style::colors::full_palette::BLUE_A100,
-    &plotters::style::colors::full_palette::BLUE_A200,
-    &plotters::style::colors::full_palette::BLUE_A400,
-    &plotters::style::colors::full_palette::BLUE_A700,
-    &plotters::style::colors::full_palette::BROWN,
-    &plotters::style::colors::full_palette::BROWN_50,
-    &plotters::style::colors::full_palette::BROWN_100,
-    &plotters::style::colors::full_palette::BROWN_200,
-    &plotters::style::colors::full_palette::BROWN_300,
-    &plotters::style::colors::full_palette::BROWN_400,
-    &plotters::style::colors::full_palette::BROWN_500,
-    &plotters::style::colors::full_palette::BROWN_600,
-    &plotters::style::colors::full_palette::BROWN_700,
-    &plotters::style::colors::full_palette::BROWN_800,
-    &plotters::style::colors::full_palette::BROWN_900,
-    &plotters::style::colors::full_palette::BROWN_A100,
-    &plotters::style::colors::full_palette::BROWN_A200,
-    &plotters::style::colors::full_palette::BROWN_A400,
-    &plotters::style::colors::full_palette::BROWN_A700,
-    &plotters::style::colors::full_palette::CYAN,
-    &plotters::style::colors::full_palette::CYAN_50,
-    &plotters::style::colors::full_palette::CYAN_100,
-    &plotters::style::colors::full_palette::CYAN_200,
-    &plotters::style::colors::full_palette::CYAN_300,
-    &plotters::style::colors::full_palette::CYAN_400,
-    &plotters::style::colors::full_palette::CYAN_500,
-    &plotters::style::colors::full_palette::CYAN_600,
-    &plotters::style::colors::full_palette::CYAN_700,
-    &plotters::style::colors::full_palette::CYAN_800,
-    &plotters::style::colors::full_palette::CYAN_900,
-    &plotters::style::colors::full_palette::CYAN_A100,
-    &plotters::style::colors::full_palette::CYAN_A200,
-    &plotters::style::colors::full_palette::CYAN_A400,
-    &plotters::style::colors::full_palette::CYAN_A700,
-    &plotters::style::colors::full_palette::DEEPORANGE,
-    &plotters::style::colors::full_palette::DEEPORANGE_50,
-    &plotters::style::colors::full_palette::DEEPORANGE_100,
-    &plotters::style::colors::full_palette::DEEPORANGE_200,
-    &plotters::style::colors::full_palette::DEEPORANGE_300,
-    &plotters::style::colors::full_palette::DEEPORANGE_400,
-    &plotters::style::colors::full_palette::DEEPORANGE_500,
-    &plotters::style::colors::full_palette::DEEPORANGE_600,
-    &plotters::style::colors::full_palette::DEEPORANGE_700,
-    &plotters::style::colors::full_palette::DEEPORANGE_800,
-    &plotters::style::colors::full_palette::DEEPORANGE_900,
-    &plotters::style::colors::full_palette::DEEPORANGE_A100,
-    &plotters::style::colors::full_palette::DEEPORANGE_A200,
-    &plotters::style::colors::full_palette::DEEPORANGE_A400,
-    &plotters::style::colors::full_palette::DEEPORANGE_A700,
-    &plotters::style::colors::full_palette::DEEPPURPLE,
-    &plotters::style::colors::full_palette::DEEPPURPLE_50,
-    &plotters::style::colors::full_palette::DEEPPURPLE_100,
-    &plotters::style::colors::full_palette::DEEPPURPLE_200,
-    &plotters::style::colors::full_palette::DEEPPURPLE_300,
-    &plotters::style::colors::full_palette::DEEPPURPLE_400,
-    &plotters::style::colors::full_palette::DEEPPURPLE_500,
-    &plotters::style::colors::full_palette::DEEPPURPLE_600,
-    &plotters::style::colors::full_palette::DEEPPURPLE_700,
-    &plotters::style::colors::full_palette::DEEPPURPLE_800,
-    &plotters::style::colors::full_palette::DEEPPURPLE_900,
-    &plotters::style::colors::full_palette::DEEPPURPLE_A100,
-    &plotters::style::colors::full_palette::DEEPPURPLE_A200,
-    &plotters::style::colors::full_palette::DEEPPURPLE_A400,
-    &plotters::style::colors::full_palette::DEEPPURPLE_A700,
-    &plotters::style::colors::full_palette::GREEN,
-    &plotters::style::colors::full_palette::GREEN_50,
-    &plotters::style::colors::full_palette::GREEN_100,
-    &plotters::style::colors::full_palette::GREEN_200,
-    &plotters::style::colors::full_palette::GREEN_300,
-    &plotters::style::colors::full_palette::GREEN_400,
-    &plotters::style::colors::full_palette::GREEN_500,
-    &plotters::style::colors::full_palette::GREEN_600,
-    &plotters::style::colors::full_palette::GREEN_700,
-    &plotters::style::colors::full_palette::GREEN_800,
-    &plotters::style::colors::full_palette::GREEN_900,
-    &plotters::style::colors::full_palette::GREEN_A100,
-    &plotters::style::colors::full_palette::GREEN_A200,
-    &plotters::style::colors::full_palette::GREEN_A400,
-    &plotters::style::colors::full_palette::GREEN_A700,
-    &plotters::style::colors::full_palette::GREY,
-    &plotters::style::colors::full_palette::GREY_50,
-    &plotters::style::colors::full_palette::GREY_100,
-    &plotters::style::colors::full_palette::GREY_200,
-    &plotters::style::colors::full_palette::GREY_300,
-    &plotters::style::colors::full_palette::GREY_400,
-    &plotters::style::colors::full_palette::GREY_500,
-    &plotters::style::colors::full_palette::GREY_600,
-    &plotters::style::colors::full_palette::GREY_700,
-    &plotters::style::colors::full_palette::GREY_800,
-    &plotters::style::colors::full_palette::GREY_900,
-    &plotters::style::colors::full_palette::GREY_A100,
-    &plotters::style::colors::full_palette::GREY_A200,
-    &plotters::style::colors::full_palette::GREY_A400,
-    &plotters::style::colors::full_palette::GREY_A700,
-    &plotters::style::colors::full_palette::INDIGO,
-    &plotters::style::colors::full_palette::INDIGO_50,
-    &plotters::style::colors::full_palette::INDIGO_100,
-    &plotters::style::colors::full_palette::INDIGO_200,
-    &plotters::style::colors::full_palette::INDIGO_300,
-    &plotters::style::colors::full_palette::INDIGO_400,
-    &plotters::style::colors::full_palette::INDIGO_500,
-    &plotters::style::colors::full_palette::INDIGO_600,
-    &plotters::style::colors::full_palette::INDIGO_700,
-    &plotters::style::colors::full_palette::INDIGO_800,
-    &plotters::style::colors::full_palette::INDIGO_900,
-    &plotters::style::colors::full_palette::INDIGO_A100,
-    &plotters::style::colors::full_palette::INDIGO_A200,
-    &plotters::style::colors::full_palette::INDIGO_A400,
-    &plotters::style::colors::full_palette::INDIGO_A700,
-    &plotters::style::colors::full_palette::LIGHTBLUE,
-    &plotters::style::colors::full_palette::LIGHTBLUE_50,
-    &plotters::style::colors::full_palette::LIGHTBLUE_100,
-    &plotters::style::colors::full_palette::LIGHTBLUE_200,
-    &plotters::style::colors::full_palette::LIGHTBLUE_300,
-    &plotters::style::colors::full_palette::LIGHTBLUE_400,
-    &plotters::style::colors::full_palette::LIGHTBLUE_500,
-    &plotters::style::colors::full_palette::LIGHTBLUE_600,
-    &plotters::style::colors::full_palette::LIGHTBLUE_700,
-    &plotters::style::colors::full_palette::LIGHTBLUE_800,
-    &plotters::style::colors::full_palette::LIGHTBLUE_900,
-    &plotters::style::colors::full_palette::LIGHTBLUE_A100,
-    &plotters::style::colors::full_palette::LIGHTBLUE_A200,
-    &plotters::style::colors::full_palette::LIGHTBLUE_A400,
-    &plotters::style::colors::full_palette::LIGHTBLUE_A700,
-    &plotters::style::colors::full_palette::LIGHTGREEN,
-    &plotters::style::colors::full_palette::LIGHTGREEN_50,
-    &plotters::style::colors::full_palette::LIGHTGREEN_100,
-    &plotters::style::colors::full_palette::LIGHTGREEN_200,
-    &plotters::style::colors::full_palette::LIGHTGREEN_300,
-    &plotters::style::colors::full_palette::LIGHTGREEN_400,
-    &plotters::style::colors::full_palette::LIGHTGREEN_500,
-    &plotters::style::colors::full_palette::LIGHTGREEN_600,
-    &plotters::style::colors::full_palette::LIGHTGREEN_700,
-    &plotters::style::colors::full_palette::LIGHTGREEN_800,
-    &plotters::style::colors::full_palette::LIGHTGREEN_900,
-    &plotters::style::colors::full_palette::LIGHTGREEN_A100,
-    &plotters::style::colors::full_palette::LIGHTGREEN_A200,
-    &plotters::style::colors::full_palette::LIGHTGREEN_A400,
-    &plotters::style::colors::full_palette::LIGHTGREEN_A700,
-    &plotters::style::colors::full_palette::LIME,
-    &plotters::style::colors::full_palette::LIME_50,
-    &plotters::style::colors::full_palette::LIME_100,
-    &plotters::style::colors::full_palette::LIME_200,
-    &plotters::style::colors::full_palette::LIME_300,
-    &plotters::style::colors::full_palette::LIME_400,
-    &plotters::style::colors::full_palette::LIME_500,
-    &plotters::style::colors::full_palette::LIME_600,
-    &plotters::style::colors::full_palette::LIME_700,
-    &plotters::style::colors::full_palette::LIME_800,
-    &plotters::style::colors::full_palette::LIME_900,
-    &plotters::style::colors::full_palette::LIME_A100,
-    &plotters::style::colors::full_palette::LIME_A200,
-    &plotters::style::colors::full_palette::LIME_A400,
-    &plotters::style::colors::full_palette::LIME_A700,
-    &plotters::style::colors::full_palette::ORANGE,
-    &plotters::style::colors::full_palette::ORANGE_50,
-    &plotters::style::colors::full_palette::ORANGE_100,
-    &plotters::style::colors::full_palette::ORANGE_200,
-    &plotters::style::colors::full_palette::ORANGE_300,
-    &plotters::style::colors::full_palette::ORANGE_400,
-    &plotters::style::colors::full_palette::ORANGE_500,
-    &plotters::style::colors::full_palette::ORANGE_600,
-    &plotters::style::colors::full_palette::ORANGE_700,
-    &plotters::style::colors::full_palette::ORANGE_800,
-    &plotters::style::colors::full_palette::ORANGE_900,
-    &plotters::style::colors::full_palette::ORANGE_A100,
-    &plotters::style::colors::full_palette::ORANGE_A200,
-    &plotters::style::colors::full_palette::ORANGE_A400,
-    &plotters::style::colors::full_palette::ORANGE_A700,
-    &plotters::style::colors::full_palette::PINK,
-    &plotters::style::colors::full_palette::PINK_50,
-    &plotters::style::colors::full_palette::PINK_100,
-    &plotters::style::colors::full_palette::PINK_200,
-    &plotters::style::colors::full_palette::PINK_300,
-    &plotters::style::colors::full_palette::PINK_400,
-    &plotters::style::colors::full_palette::PINK_500,
-    &plotters::style::colors::full_palette::PINK_600,
-    &plotters::style::colors::full_palette::PINK_700,
-    &plotters::style::colors::full_palette::PINK_800,
-    &plotters::style::colors::full_palette::PINK_900,
-    &plotters::style::colors::full_palette::PINK_A100,
-    &plotters::style::colors::full_palette::PINK_A200,
-    &plotters::style::colors::full_palette::PINK_A400,
-    &plotters::style::colors::full_palette::PINK_A700,
-    &plotters::style::colors::full_palette::PURPLE,
-    &plotters::style::colors::full_palette::PURPLE_50,
-    &plotters::style::colors::full_palette::PURPLE_100,
-    &plotters::style::colors::full_palette::PURPLE_200,
-    &plotters::style::colors::full_palette::PURPLE_300,
-    &plotters::style::colors::full_palette::PURPLE_400,
-    &plotters::style::colors::full_palette::PURPLE_500,
-    &plotters::style::colors::full_palette::PURPLE_600,
-    &plotters::style::colors::full_palette::PURPLE_700,
-    &plotters::style::colors::full_palette::PURPLE_800,
-    &plotters::style::colors::full_palette::PURPLE_900,
-    &plotters::style::colors::full_palette::PURPLE_A100,
-    &plotters::style::colors::full_palette::PURPLE_A200,
-    &plotters::style::colors::full_palette::PURPLE_A400,
-    &plotters::style::colors::full_palette::PURPLE_A700,
-    &plotters::style::colors::full_palette::RED,
-    &plotters::style::colors::full_palette::RED_50,
-    &plotters::style::colors::full_palette::RED_100,
-    &plotters::style::colors::full_palette::RED_200,
-    &plotters::style::colors::full_palette::RED_300,
-    &plotters::style::colors::full_palette::RED_400,
-    &plotters::style::colors::full_palette::RED_500,
-    &plotters::style::colors::full_palette::RED_600,
-    &plotters::style::colors::full_palette::RED_700,
-    &plotters::style::colors::full_palette::RED_800,
-    &plotters::style::colors::full_palette::RED_900,
-    &plotters::style::colors::full_palette::RED_A100,
-    &plotters::style::colors::full_palette::RED_A200,
-    &plotters::style::colors::full_palette::RED_A400,
-    &plotters::style::colors::full_palette::RED_A700,
-    &plotters::style::colors::full_palette::TEAL,
-    &plotters::style::colors::full_palette::TEAL_50,
-    &plotters::style::colors::full_palette::TEAL_100,
-    &plotters::style::colors::full_palette::TEAL_200,
-    &plotters::style::colors::full_palette::TEAL_300,
-    &plotters::style::colors::full_palette::TEAL_400,
-    &plotters::style::colors::full_palette::TEAL_500,
-    &plotters::style::colors::full_palette::TEAL_600,
-    &plotters::style::colors::full_palette::TEAL_700,
-    &plotters::style::colors::full_palette::TEAL_800,
-    &plotters::style::colors::full_palette::TEAL_900,
-    &plotters::style::colors::full_palette::TEAL_A100,
-    &plotters::style::colors::full_palette::TEAL_A200,
-    &plotters::style::colors::full_palette::TEAL_A400,
-    &plotters::style::colors::full_palette::TEAL_A700,
-    &plotters::style::colors::full_palette::WHITE,
-    &plotters::style::colors::full_palette::YELLOW,
-    &plotters::style::colors::full_palette::YELLOW_50,
-    &plotters::style::colors::full_palette::YELLOW_100,
-    &plotters::style::colors::full_palette::YELLOW_200,
-    &plotters::style::colors::full_palette::YELLOW_300,
-    &plotters::style::colors::full_palette::YELLOW_400,
-    &plotters::style::colors::full_palette::YELLOW_500,
-    &plotters::style::colors::full_palette::YELLOW_600,
-    &plotters::style::colors::full_palette::YELLOW_700,
-    &plotters::style::colors::full_palette::YELLOW_800,
-    &plotters::style::colors::full_palette::YELLOW_900,
-    &plotters::style::colors::full_palette::YELLOW_A100,
-    &plotters::style::colors::full_palette::YELLOW_A200,
-    &plotters::style::colors::full_palette::YELLOW_A400,
-    &plotters::style::colors::full_palette::YELLOW_A700,
-    &plotters::style::colors::full_palette::AMBER,
-    &plotters::style::colors::full_palette::AMBER_50,
-    &plotters::style::colors::full_palette::AMBER_100,
-    &plotters::style::colors::full_palette::AMBER_200,
-    &plotters::style::colors::full_palette::AMBER_300,
-    &plotters::style::colors::full_palette::AMBER_400,
-    &plotters::style::colors::full_palette::AMBER_500,
-    &plotters::style::colors::full_palette::AMBER_600,
-    &plotters::style::colors::full_palette::AMBER_700,
-    &plotters::style::colors::full_palette::AMBER_800,
-    &plotters::style::colors::full_palette::AMBER_900,
-    &plotters::style::colors::full_palette::AMBER_A100,
-    &plotters::style::colors::full_palette::AMBER_A200,
-    &plotters::style::colors::full_palette::AMBER_A400,
-    &plotters::style::colors::full_palette::AMBER_A700,
+const VIRIDIS: [(u8, u8, u8); 9] = [
+    (0x44, 0x01, 0x54),
+    (0x47, 0x2d, 0x7b),
+    (0x3b, 0x52, 0x8b),
+    (0x2c, 0x72, 0x8e),
+    (0x21, 0x91, 0x8c),
+    (0x28, 0xae, 0x80),
+    (0x5e, 0xc9, 0x62),
+    (0xad, 0xdc, 0x30),
+    (0xfd, 0xe7, 0x25),
+];
+
+const MAGMA: [(u8, u8, u8); 9] = [
+    (0x00, 0x00, 0x04),
+    (0x1c, 0x10, 0x44),
+    (0x4f, 0x12, 0x7b),
+    (0x81, 0x25, 0x81),
+    (0xb5, 0x36, 0x7a),
+    (0xe5, 0x50, 0x64),
+    (0xfb, 0x87, 0x61),
+    (0xfe, 0xc2, 0x87),
+    (0xfc, 0xfd, 0xbf),
+];
+
+const PLASMA: [(u8, u8, u8); 9] = [
+    (0x0d, 0x08, 0x87),
+    (0x4c, 0x02, 0xa1),
+    (0x7e, 0x03, 0xa8),
+    (0xa9, 0x23, 0x95),
+    (0xcc, 0x47, 0x78),
+    (0xe5, 0x6b, 0x5d),
+    (0xf8, 0x94, 0x41),
+    (0xfd, 0xc3, 0x28),
+    (0xf0, 0xf9, 0x21),
+];
+
+const OKABE_ITO: [(u8, u8, u8); 7] = [
+    (0xe6, 0x9f, 0x00),
+    (0x56, 0xb4, 0xe9),
+    (0x00, 0x9e, 0x73),
+    (0xf0, 0xe4, 0x42),
+    (0x00, 0x72, 0xb2),
+    (0xd5, 0x5e, 0x00),
+    (0xcc, 0x79, 0xa7),
+];
+
+const TABLEAU_10: [(u8, u8, u8); 10] = [
+    (0x4e, 0x79, 0xa7),
+    (0xf2, 0x8e, 0x2b),
+    (0xe1, 0x57, 0x59),
+    (0x76, 0xb7, 0xb2),
+    (0x59, 0xa1, 0x4f),
+    (0xed, 0xc9, 0x48),
+    (0xb0, 0x7a, 0xa1),
+    (0xff, 0x9d, 0xa7),
+    (0x9c, 0x75, 0x5f),
+    (0xba, 0xb0, 0xac),
+];
+
+const SET1: [(u8, u8, u8); 9] = [
+    (0xe4, 0x1a, 0x1c),
+    (0x37, 0x7e, 0xb8),
+    (0x4d, 0xaf, 0x4a),
+    (0x98, 0x4e, 0xa3),
+    (0xff, 0x7f, 0x00),
+    (0xff, 0xff, 0x33),
+    (0xa6, 0x56, 0x28),
+    (0xf7, 0x81, 0xbf),
+    (0x99, 0x99, 0x99),
 ];
+
+const SET2: [(u8, u8, u8); 8] = [
+    (0x66, 0xc2, 0xa5),
+    (0xfc, 0x8d, 0x62),
+    (0x8d, 0xa0, 0xcb),
+    (0xe7, 0x8a, 0xc3),
+    (0xa6, 0xd8, 0x54),
+    (0xff, 0xd9, 0x2f),
+    (0xe5, 0xc4, 0x94),
+    (0xb3, 0xb3, 0xb3),
+];
+
+const DARK2: [(u8, u8, u8); 8] = [
+    (0x1b, 0x9e, 0x77),
+    (0xd9, 0x5f, 0x02),
+    (0x75, 0x70, 0xb3),
+    (0xe7, 0x29, 0x8a),
+    (0x66, 0xa6, 0x1e),
+    (0xe6, 0xab, 0x02),
+    (0xa6, 0x76, 0x1d),
+    (0x66, 0x66, 0x66),
+];
+
+const PAIRED: [(u8, u8, u8); 12] = [
+    (0xa6, 0xce, 0xe3),
+    (0x1f, 0x78, 0xb4),
+    (0xb2, 0xdf, 0x8a),
+    (0x33, 0xa0, 0x2c),
+    (0xfb, 0x9a, 0x99),
+    (0xe3, 0x1a, 0x1c),
+    (0xfd, 0xbf, 0x6f),
+    (0xff, 0x7f, 0x00),
+    (0xca, 0xb2, 0xd6),
+    (0x6a, 0x3d, 0x9a),
+    (0xff, 0xff, 0x99),
+    (0xb1, 0x59, 0x28),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use plotters::style::Color;
+
+    #[test]
+    fn test_contrasting() {
+        assert_eq!(contrasting(WHITE.to_rgba()), &BLACK);
+        assert_eq!(contrasting(RGBAColor(0x44, 0x01, 0x54, 1.0)), &WHITE);
+    }
+
+    #[test]
+    fn test_palette() {
+        let p: Palette = serde_yaml::from_str("tableau10").unwrap();
+        let cs: Vec<_> = p.iter().take(11).collect();
+        assert_eq!(cs[0], RGBAColor(0x4e, 0x79, 0xa7, 1.0));
+        assert_eq!(cs[10], cs[0]);
+        let p: Palette = serde_yaml::from_str("[red, '#00ff00']").unwrap();
+        let cs: Vec<_> = p.iter().take(3).collect();
+        assert_eq!(cs[1], RGBAColor(0, 255, 0, 1.0));
+        assert_eq!(cs[2], RGBAColor(255, 0, 0, 1.0));
+        let p: Palette = serde_yaml::from_str("[]").unwrap();
+        assert_eq!(p.iter().next(), Palette::default().iter().next());
+    }
+}
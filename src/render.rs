@@ -1,10 +1,12 @@
 use plotters::{
-    prelude::{ChartBuilder, DrawingBackend},
+    coord::Shift,
+    prelude::{DrawingArea, DrawingBackend},
     style::RGBAColor,
 };
+use plotters_backend::BackendColor;
 use std::fmt::Debug;
 
-use plotters::style::{FontFamily, TextStyle};
+use plotters::style::{FontFamily, FontStyle, TextStyle};
 use serde::Deserialize;
 
 pub type Colour = plotters::style::RGBAColor;
@@ -14,13 +16,13 @@ pub struct CssColour(css_color_parser::Color);
 
 impl CssColour {
     pub fn as_rgba(&self) -> Colour {
-        self.clone().into()
+        (*self).into()
     }
 }
 
-impl Into<Colour> for CssColour {
-    fn into(self) -> Colour {
-        RGBAColor(self.0.r, self.0.g, self.0.b, self.0.a as f64)
+impl From<CssColour> for Colour {
+    fn from(val: CssColour) -> Self {
+        RGBAColor(val.0.r, val.0.g, val.0.b, val.0.a as f64)
     }
 }
 mod colour_de {
@@ -45,6 +47,8 @@ impl Default for FontInfo {
         Self {
             family: FontFamily::SansSerif.into(),
             size: 12f64,
+            weight: None,
+            style: None,
         }
     }
 }
@@ -52,15 +56,13 @@ impl Default for FontInfo {
 pub struct FontStore(String);
 impl FontStore {
     pub fn family<'a>(&'a self) -> FontFamily<'a> {
-        FontFamily::Name(self.0.as_str())
+        self.0.as_str().into()
     }
 }
 
-#[derive(Clone, Deserialize)]
+#[derive(Clone)]
 pub enum FontType {
-    #[serde(with = "font_family_serde")]
     Store(FontStore),
-    #[serde(skip)]
     Family(FontFamily<'static>),
 }
 impl From<FontFamily<'static>> for FontType {
@@ -76,31 +78,85 @@ impl Debug for FontType {
         }
     }
 }
+impl<'de> Deserialize<'de> for FontType {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        Ok(FontType::Store(FontStore(String::deserialize(
+            deserializer,
+        )?)))
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FontWeight {
+    Normal,
+    Bold,
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FontSlant {
+    Normal,
+    Italic,
+    Oblique,
+}
 
 #[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
 pub struct FontInfo {
+    /// Name of an installed family or one loaded from `font_files`, or serif, sans-serif or
+    /// monospace. default: sans-serif
     pub family: FontType,
+    /// default: 12
     pub size: f64,
+    /// Bold can't be combined with italic or oblique, bold wins. default: normal
+    weight: Option<FontWeight>,
+    /// default: normal
+    style: Option<FontSlant>,
 }
 impl FontInfo {
-    pub fn into_text_style<'a>(&'a self) -> TextStyle<'a> {
+    fn font_style(&self) -> FontStyle {
+        match (self.weight, self.style) {
+            (Some(FontWeight::Bold), _) => FontStyle::Bold,
+            (_, Some(FontSlant::Italic)) => FontStyle::Italic,
+            (_, Some(FontSlant::Oblique)) => FontStyle::Oblique,
+            _ => FontStyle::Normal,
+        }
+    }
+
+    pub fn to_text_style<'a>(&'a self) -> TextStyle<'a> {
         (
             match &self.family {
                 FontType::Store(s) => s.family(),
-                FontType::Family(f) => f.clone(),
+                FontType::Family(f) => *f,
             },
             self.size,
+            self.font_style(),
         )
             .into()
     }
 }
 
+/// Colour text in `style` is drawn in
+pub fn text_colour(style: &TextStyle) -> Colour {
+    let BackendColor {
+        rgb: (r, g, b),
+        alpha,
+    } = style.color;
+    RGBAColor(r, g, b, alpha)
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error("failed to load font {0}")]
     FontLoading(String),
     #[error("plotter drawing error: {0}")]
     PlottersDraw(String),
+    #[error("invalid chart: {0}")]
+    InvalidChart(String),
 }
 impl<E: std::error::Error + Send + Sync> From<plotters::drawing::DrawingAreaErrorKind<E>>
     for Error
@@ -113,34 +169,23 @@ impl<E: std::error::Error + Send + Sync> From<plotters::drawing::DrawingAreaErro
 unsafe impl Send for Error {}
 unsafe impl Sync for Error {}
 
-mod font_family_serde {
-
-    use serde::{de::Error, Deserialize, Deserializer};
-
-    use css_color_parser::Color;
+pub trait Render {
+    type Error: std::error::Error;
+    fn render<DB: DrawingBackend>(&self, root: &DrawingArea<DB, Shift>) -> Result<(), Self::Error>;
+}
 
-    use super::FontStore;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    // The signature of a deserialize_with function must follow the pattern:
-    //
-    //    fn deserialize<'de, D>(D) -> Result<T, D::Error>
-    //    where
-    //        D: Deserializer<'de>
-    //
-    // although it may also be generic over the output types T.
-    pub fn deserialize<'de, D>(deserializer: D) -> Result<FontStore, D::Error>
-    where
-        D: Deserializer<'de>,
-    {
-        let s = String::deserialize(deserializer)?;
-        let _c = s
-            .parse::<Color>()
-            .map_err(|e| D::Error::custom(e.to_string()))?;
-        Ok(FontStore(s))
+    #[test]
+    fn test_font_info() {
+        let f: FontInfo = serde_yaml::from_str("{family: Noto Sans, weight: bold}").unwrap();
+        assert_eq!(f.to_text_style().font.get_name(), "Noto Sans");
+        assert_eq!(f.to_text_style().font.get_style().as_str(), "bold");
+        assert_eq!(f.size, 12.0);
+        let f: FontInfo = serde_yaml::from_str("{family: serif, style: italic}").unwrap();
+        assert_eq!(f.to_text_style().font.get_name(), "serif");
+        assert_eq!(f.to_text_style().font.get_style().as_str(), "italic");
     }
 }
-
-pub trait Render {
-    type Error: std::error::Error;
-    fn render<DB: DrawingBackend>(&self, r: &mut ChartBuilder<DB>) -> Result<(), Self::Error>;
-}
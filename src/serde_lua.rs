@@ -16,8 +16,6 @@ pub fn from_lua<'de, V: Deserialize<'de>>(v: Value) -> Result<V, DeErr> {
     V::deserialize(de)
 }
 
-impl<'de, 'lua> Deserializer<'lua> {}
-
 #[derive(Error, Debug)]
 pub enum LuaDeserializeErr {
     #[error("lua error {0}")]
@@ -61,6 +59,7 @@ impl From<rlua::Error> for LuaDeserializeErr {
         Self::Lua(e)
     }
 }
+#[allow(unused, clippy::wrong_self_convention)]
 trait ValueExt<'lua> {
     fn as_table<F: FnOnce(rlua::Table) -> Result<R, LuaDeserializeErr>, R>(
         self,
@@ -96,6 +95,7 @@ impl<'lua> ValueExt<'lua> for Value<'lua> {
         }
     }
 }
+#[allow(unused)]
 struct Enum<'lua> {
     v: rlua::String<'lua>,
 }
@@ -111,7 +111,7 @@ impl<'de, 'lua> de::EnumAccess<'de> for Enum<'lua> {
         V: de::DeserializeSeed<'de>,
     {
         let val = seed.deserialize(Deserializer {
-            input: Value::String(self.v.clone().into()),
+            input: Value::String(self.v.clone()),
         })?;
         Ok((val, self))
     }
@@ -158,12 +158,7 @@ struct TableMap<'lua> {
 }
 impl<'lua> TableSeq<'lua> {
     fn new(tbl: rlua::Table<'lua>) -> Self {
-        let mut values: Vec<_> = tbl
-            .sequence_values()
-            .into_iter()
-            .filter(|r| r.is_ok())
-            .map(|r| r.unwrap())
-            .collect();
+        let mut values: Vec<_> = tbl.sequence_values().flatten().collect();
         values.reverse();
         Self { values }
     }
@@ -171,16 +166,14 @@ impl<'lua> TableSeq<'lua> {
 
 impl<'lua> TableMap<'lua> {
     fn new(tbl: rlua::Table<'lua>) -> Self {
-        let (mut keys, mut values) = tbl
-            .pairs()
-            .into_iter()
-            .filter(|k| k.is_ok())
-            .map(|k| k.unwrap())
-            .fold((Vec::new(), Vec::new()), |(mut ks, mut vs), (k, v)| {
-                ks.push(k);
-                vs.push(v);
-                (ks, vs)
-            });
+        let (mut keys, mut values) =
+            tbl.pairs()
+                .flatten()
+                .fold((Vec::new(), Vec::new()), |(mut ks, mut vs), (k, v)| {
+                    ks.push(k);
+                    vs.push(v);
+                    (ks, vs)
+                });
         keys.reverse();
         values.reverse();
 
@@ -219,7 +212,7 @@ impl<'de, 'lua> de::SeqAccess<'de> for TableSeq<'lua> {
     where
         T: de::DeserializeSeed<'de>,
     {
-        if self.values.len() == 0 {
+        if self.values.is_empty() {
             Ok(None)
         } else {
             let v = self.values.pop().unwrap();
@@ -257,10 +250,12 @@ impl<'de, 'lua> de::Deserializer<'de> for Deserializer<'lua> {
     tuple_struct map struct enum identifier ignored_any}
 }
 
+#[allow(unused)]
 struct Serializer<'lua> {
     lua: rlua::Context<'lua>,
 }
 
+#[allow(unused, clippy::wrong_self_convention)]
 impl<'lua> Serializer<'lua> {
     fn new(lua: rlua::Context<'lua>) -> Self {
         Self { lua }
@@ -271,6 +266,7 @@ impl<'lua> Serializer<'lua> {
     }
 }
 
+#[allow(unused)]
 struct SeqSerializer<'lua> {
     vals: Vec<Value<'lua>>,
     ctx: rlua::Context<'lua>,
@@ -280,12 +276,11 @@ impl<'lua> ser::SerializeSeq for SeqSerializer<'lua> {
     type Ok = Value<'lua>;
     type Error = DeErr;
 
-    fn serialize_element<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
     where
-        T: serde::Serialize,
+        T: serde::Serialize + ?Sized,
     {
-        self.vals
-            .push(value.serialize(Serializer::new(self.ctx.clone()))?);
+        self.vals.push(value.serialize(Serializer::new(self.ctx))?);
         Ok(())
     }
 
@@ -300,9 +295,9 @@ impl<'a> ser::SerializeTuple for SeqSerializer<'a> {
     type Ok = Value<'a>;
     type Error = DeErr;
 
-    fn serialize_element<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
     where
-        T: serde::Serialize,
+        T: serde::Serialize + ?Sized,
     {
         <Self as ser::SerializeSeq>::serialize_element(self, value)
     }
@@ -315,9 +310,9 @@ impl<'lua> ser::SerializeTupleStruct for SeqSerializer<'lua> {
     type Ok = Value<'lua>;
     type Error = DeErr;
 
-    fn serialize_field<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Self::Error>
     where
-        T: serde::Serialize,
+        T: serde::Serialize + ?Sized,
     {
         <Self as ser::SerializeSeq>::serialize_element(self, value)
     }
@@ -330,9 +325,9 @@ impl<'lua> ser::SerializeTupleVariant for SeqSerializer<'lua> {
     type Ok = Value<'lua>;
     type Error = DeErr;
 
-    fn serialize_field<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Self::Error>
     where
-        T: serde::Serialize,
+        T: serde::Serialize + ?Sized,
     {
         <Self as ser::SerializeSeq>::serialize_element(self, value)
     }
@@ -341,6 +336,7 @@ impl<'lua> ser::SerializeTupleVariant for SeqSerializer<'lua> {
         <Self as ser::SerializeSeq>::end(self)
     }
 }
+#[allow(unused)]
 struct MapSerialize<'lua> {
     lua: rlua::Context<'lua>,
     keys: Vec<Value<'lua>>,
@@ -350,27 +346,26 @@ impl<'lua> ser::SerializeMap for MapSerialize<'lua> {
     type Ok = Value<'lua>;
     type Error = DeErr;
 
-    fn serialize_key<T: ?Sized>(&mut self, key: &T) -> Result<(), Self::Error>
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), Self::Error>
     where
-        T: serde::Serialize,
+        T: serde::Serialize + ?Sized,
     {
-        self.keys
-            .push(key.serialize(Serializer::new(self.lua.clone()))?);
+        self.keys.push(key.serialize(Serializer::new(self.lua))?);
         Ok(())
     }
 
-    fn serialize_value<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), Self::Error>
     where
-        T: serde::Serialize,
+        T: serde::Serialize + ?Sized,
     {
         self.values
-            .push(value.serialize(Serializer::new(self.lua.clone()))?);
+            .push(value.serialize(Serializer::new(self.lua))?);
         Ok(())
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
         Ok(Value::Table(self.lua.create_table_from(
-            self.keys.into_iter().zip(self.values.into_iter()),
+            self.keys.into_iter().zip(self.values),
         )?))
     }
 }
@@ -378,13 +373,9 @@ impl<'lua> ser::SerializeStruct for MapSerialize<'lua> {
     type Ok = Value<'lua>;
     type Error = DeErr;
 
-    fn serialize_field<T: ?Sized>(
-        &mut self,
-        key: &'static str,
-        value: &T,
-    ) -> Result<(), Self::Error>
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error>
     where
-        T: serde::Serialize,
+        T: serde::Serialize + ?Sized,
     {
         <Self as SerializeMap>::serialize_key(self, key)?;
         <Self as SerializeMap>::serialize_value(self, value)?;
@@ -399,13 +390,9 @@ impl<'lua> ser::SerializeStructVariant for MapSerialize<'lua> {
     type Ok = Value<'lua>;
     type Error = DeErr;
 
-    fn serialize_field<T: ?Sized>(
-        &mut self,
-        key: &'static str,
-        value: &T,
-    ) -> Result<(), Self::Error>
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error>
     where
-        T: serde::Serialize,
+        T: serde::Serialize + ?Sized,
     {
         <Self as SerializeMap>::serialize_key(self, key)?;
         <Self as SerializeMap>::serialize_value(self, value)?;
@@ -496,9 +483,9 @@ impl<'lua> serde::ser::Serializer for Serializer<'lua> {
         Ok(Value::Nil)
     }
 
-    fn serialize_some<T: ?Sized>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
     where
-        T: serde::Serialize,
+        T: serde::Serialize + ?Sized,
     {
         value.serialize(self)
     }
@@ -520,18 +507,18 @@ impl<'lua> serde::ser::Serializer for Serializer<'lua> {
         Ok(Value::Nil)
     }
 
-    fn serialize_newtype_struct<T: ?Sized>(
+    fn serialize_newtype_struct<T>(
         self,
         _name: &'static str,
         value: &T,
     ) -> Result<Self::Ok, Self::Error>
     where
-        T: serde::Serialize,
+        T: serde::Serialize + ?Sized,
     {
         value.serialize(self)
     }
 
-    fn serialize_newtype_variant<T: ?Sized>(
+    fn serialize_newtype_variant<T>(
         self,
         _name: &'static str,
         _variant_index: u32,
@@ -539,17 +526,16 @@ impl<'lua> serde::ser::Serializer for Serializer<'lua> {
         _value: &T,
     ) -> Result<Self::Ok, Self::Error>
     where
-        T: serde::Serialize,
+        T: serde::Serialize + ?Sized,
     {
         todo!()
     }
 
     fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
-        let mut vals = Vec::new();
-        vals.reserve(len.unwrap_or(0));
+        let vals = Vec::with_capacity(len.unwrap_or(0));
         Ok(SeqSerializer {
             vals,
-            ctx: self.lua.clone(),
+            ctx: self.lua,
         })
     }
 
@@ -585,7 +571,7 @@ impl<'lua> serde::ser::Serializer for Serializer<'lua> {
         len: usize,
     ) -> Result<Self::SerializeStruct, Self::Error> {
         Ok(MapSerialize {
-            lua: self.lua.clone(),
+            lua: self.lua,
             keys: Vec::with_capacity(len),
             values: Vec::with_capacity(len),
         })
@@ -0,0 +1,227 @@
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use plotters::chart::{MeshStyle, SecondaryMeshStyle};
+use plotters::coord::ranged1d::ValueFormatter;
+use plotters::prelude::{DrawingBackend, Ranged};
+use plotters::style::{Color, RGBAColor, TextStyle};
+use serde::Deserialize;
+
+use crate::palette::{Palette, PaletteIter};
+use crate::render::{CssColour, FontInfo};
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum BuiltinTheme {
+    #[default]
+    Light,
+    Dark,
+    /// Greys only, for printing in black and white
+    Print,
+    /// Strong colours that are easy to tell apart, including with colour blindness
+    HighContrast,
+}
+
+impl BuiltinTheme {
+    fn spec(&self) -> &'static str {
+        match self {
+            BuiltinTheme::Light => include_str!("themes/light.yaml"),
+            BuiltinTheme::Dark => include_str!("themes/dark.yaml"),
+            BuiltinTheme::Print => include_str!("themes/print.yaml"),
+            BuiltinTheme::HighContrast => include_str!("themes/high-contrast.yaml"),
+        }
+    }
+
+    fn theme(&self) -> Theme {
+        serde_yaml::from_str(self.spec()).expect("built-in themes are valid")
+    }
+}
+
+/// Colours and fonts shared by every part of a chart, anything not given is taken from the base
+#[derive(Clone, Debug, Deserialize)]
+pub struct Theme {
+    /// default: light
+    base: Option<BuiltinTheme>,
+    background: Option<CssColour>,
+    /// Colour of all text, and of annotations and error bars
+    text: Option<CssColour>,
+    /// Colour of the axis lines and ticks
+    axis: Option<CssColour>,
+    /// Colour of the grid lines, the lines between ticks are drawn at half its opacity
+    grid: Option<CssColour>,
+    legend_background: Option<CssColour>,
+    legend_border: Option<CssColour>,
+    /// Font of everything unless the chart sets a font, default: sans-serif at 12pt
+    font: Option<FontInfo>,
+    /// Colours given to datasets in turn, default: okabe-ito
+    palette: Option<Palette>,
+}
+
+/// `theme` is either the name of a built-in theme, the path to a theme file or the theme itself
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+pub enum ThemeSpec {
+    Named(String),
+    Inline(Theme),
+}
+
+impl ThemeSpec {
+    /// Load the theme, theme files are found relative to `dir`
+    pub fn load(&self, dir: &Path) -> Result<Theme> {
+        match self {
+            ThemeSpec::Named(name) => {
+                if let Ok(builtin) = serde_yaml::from_str::<BuiltinTheme>(name) {
+                    return Ok(builtin.theme());
+                }
+                let path = dir.join(name);
+                let f = std::fs::File::open(&path)
+                    .map_err(|e| anyhow!("failed to open theme {}: {}", path.display(), e))?;
+                serde_yaml::from_reader(f)
+                    .map_err(|e| anyhow!("failed to read theme {}: {}", path.display(), e))
+            }
+            ThemeSpec::Inline(t) => Ok(t.clone()),
+        }
+        .map(Theme::resolve)
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        BuiltinTheme::default().theme()
+    }
+}
+
+impl Theme {
+    /// Fill in everything this theme doesn't set from its base, so the base is only read once
+    fn resolve(self) -> Theme {
+        let base = self.base.unwrap_or_default().theme();
+        Theme {
+            base: self.base,
+            background: self.background.or(base.background),
+            text: self.text.or(base.text),
+            axis: self.axis.or(base.axis),
+            grid: self.grid.or(base.grid),
+            legend_background: self.legend_background.or(base.legend_background),
+            legend_border: self.legend_border.or(base.legend_border),
+            font: self.font.or(base.font),
+            palette: self.palette.or(base.palette),
+        }
+    }
+
+    /// `f` of the theme, which is set for all of the colours once the theme is resolved
+    fn get<T>(&self, f: impl Fn(&Theme) -> Option<T>) -> T {
+        f(self).expect("themes are resolved when they are loaded")
+    }
+
+    pub fn background(&self) -> RGBAColor {
+        self.get(|t| t.background).as_rgba()
+    }
+
+    pub fn text(&self) -> RGBAColor {
+        self.get(|t| t.text).as_rgba()
+    }
+
+    pub fn axis(&self) -> RGBAColor {
+        self.get(|t| t.axis).as_rgba()
+    }
+
+    pub fn grid(&self) -> RGBAColor {
+        self.get(|t| t.grid).as_rgba()
+    }
+
+    pub fn legend_background(&self) -> RGBAColor {
+        self.get(|t| t.legend_background).as_rgba()
+    }
+
+    pub fn legend_border(&self) -> RGBAColor {
+        self.get(|t| t.legend_border).as_rgba()
+    }
+
+    pub fn font(&self) -> FontInfo {
+        self.font.clone().unwrap_or_default()
+    }
+
+    fn palette(&self) -> Palette {
+        self.palette.clone().unwrap_or_default()
+    }
+
+    /// Colours to give datasets in turn, repeating forever
+    pub fn colours(&self) -> PaletteIter {
+        self.palette().iter()
+    }
+
+    /// `font` in the text colour
+    pub fn text_style<'a>(&self, font: &'a FontInfo) -> TextStyle<'a> {
+        TextStyle {
+            color: self.text().to_backend_color(),
+            ..font.to_text_style()
+        }
+    }
+
+    /// Set the colours of the axes and grid, and the fonts of the ticks and axis titles
+    pub fn style_mesh<'a, 'b, X: Ranged, Y: Ranged, DB: DrawingBackend>(
+        &self,
+        mesh: &mut MeshStyle<'a, 'b, X, Y, DB>,
+        ticks: &'b FontInfo,
+        labels: &'b FontInfo,
+    ) {
+        let grid = self.grid();
+        mesh.axis_style(self.axis())
+            .bold_line_style(grid)
+            .light_line_style(grid.mix(0.5))
+            .label_style(self.text_style(ticks))
+            .axis_desc_style(self.text_style(labels));
+    }
+
+    /// Set the colour of the secondary axes and the fonts of their ticks and titles
+    pub fn style_secondary_mesh<'a, 'b, X, Y, DB: DrawingBackend>(
+        &self,
+        mesh: &mut SecondaryMeshStyle<'a, 'b, X, Y, DB>,
+        ticks: &'b FontInfo,
+        labels: &'b FontInfo,
+    ) where
+        X: Ranged + ValueFormatter<X::ValueType>,
+        Y: Ranged + ValueFormatter<Y::ValueType>,
+    {
+        mesh.axis_style(self.axis())
+            .label_style(self.text_style(ticks))
+            .axis_desc_style(self.text_style(labels));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_themes() {
+        for builtin in [
+            BuiltinTheme::Light,
+            BuiltinTheme::Dark,
+            BuiltinTheme::Print,
+            BuiltinTheme::HighContrast,
+        ] {
+            let t = builtin.theme();
+            assert!(t.background.is_some() && t.text.is_some() && t.axis.is_some());
+            assert!(t.grid.is_some());
+            assert!(t.legend_background.is_some() && t.legend_border.is_some());
+        }
+    }
+
+    #[test]
+    fn test_theme_base() {
+        let spec: ThemeSpec = serde_yaml::from_str("{base: dark, text: red}").unwrap();
+        let t = spec.load(Path::new(".")).unwrap();
+        assert_eq!(t.text(), RGBAColor(255, 0, 0, 1.0));
+        assert_eq!(t.background(), BuiltinTheme::Dark.theme().background());
+        assert!(matches!(t.palette(), Palette::Colours(_)));
+        assert!(t.grid.is_some() && t.legend_border.is_some());
+        let t = ThemeSpec::Named("light".to_owned())
+            .load(Path::new("."))
+            .unwrap();
+        assert_eq!(t.colours().next(), Palette::default().iter().next());
+        assert!(ThemeSpec::Named("missing.yaml".to_owned())
+            .load(Path::new("."))
+            .is_err());
+    }
+}
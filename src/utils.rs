@@ -1,5 +1,6 @@
 use std::ops::{Deref, DerefMut};
 
+#[allow(unused)]
 pub trait Holds {
     type Item;
     fn map<O, T, F: FnOnce(Self::Item) -> T>(self, f: F) -> O
@@ -8,6 +9,7 @@ pub trait Holds {
     fn into_value(self) -> Self::Item;
 }
 
+#[allow(unused)]
 pub trait RoundMul<T> {
     fn ceil_mul(self, other: T) -> Self;
     fn floor_mul(self, other: T) -> Self;
@@ -32,7 +34,79 @@ impl RoundMul<f64> for f64 {
     }
 }
 
-struct BezWrapper(kurbo::BezPath);
+/// Linearly interpolated quantile `q` (0..=1) of already sorted values
+pub fn quantile(sorted: &[f64], q: f64) -> f64 {
+    if sorted.is_empty() {
+        return f64::NAN;
+    }
+    let pos = q.clamp(0.0, 1.0) * (sorted.len() - 1) as f64;
+    let lower = pos.floor() as usize;
+    let upper = pos.ceil() as usize;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (pos - lower as f64)
+}
+
+pub struct BezWrapper(kurbo::BezPath);
+
+impl BezWrapper {
+    /// Monotone cubic spline through `points`, which never overshoots them. Segments where x
+    /// doesn't increase are left straight
+    pub fn monotone(points: &[kurbo::Point]) -> Self {
+        let slope = |a: kurbo::Point, b: kurbo::Point| {
+            if b.x > a.x {
+                (b.y - a.y) / (b.x - a.x)
+            } else {
+                0.0
+            }
+        };
+        let slopes: Vec<_> = points.windows(2).map(|w| slope(w[0], w[1])).collect();
+        // tangents from Steffen's method, which keeps each segment monotone
+        let tangents: Vec<_> = (0..points.len())
+            .map(|n| match (n.checked_sub(1), slopes.get(n)) {
+                (Some(prev), Some(next)) => {
+                    let (s0, s1) = (slopes[prev], *next);
+                    let (h0, h1) = (points[n].x - points[prev].x, points[n + 1].x - points[n].x);
+                    if h0 <= 0.0 || h1 <= 0.0 {
+                        return 0.0;
+                    }
+                    let p = (s0 * h1 + s1 * h0) / (h0 + h1);
+                    (s0.signum() + s1.signum()) * s0.abs().min(s1.abs()).min(0.5 * p.abs())
+                }
+                (None, Some(s)) => *s,
+                (Some(prev), None) => slopes[prev],
+                (None, None) => 0.0,
+            })
+            .collect();
+        let mut path = kurbo::BezPath::new();
+        for (n, p) in points.iter().enumerate() {
+            if n == 0 {
+                path.move_to(*p);
+                continue;
+            }
+            let prev = points[n - 1];
+            let h = p.x - prev.x;
+            if h > 0.0 {
+                path.curve_to(
+                    (prev.x + h / 3.0, prev.y + tangents[n - 1] * h / 3.0),
+                    (p.x - h / 3.0, p.y - tangents[n] * h / 3.0),
+                    (p.x, p.y),
+                );
+            } else {
+                path.line_to(*p);
+            }
+        }
+        Self(path)
+    }
+
+    /// Points of straight lines within `tolerance` of the path
+    pub fn flattened(&self, tolerance: f64) -> Vec<kurbo::Point> {
+        let mut points = Vec::new();
+        self.0.flatten(tolerance, |el| match el {
+            kurbo::PathEl::MoveTo(p) | kurbo::PathEl::LineTo(p) => points.push(p),
+            _ => {}
+        });
+        points
+    }
+}
 
 impl From<BezWrapper> for kurbo::BezPath {
     fn from(r: BezWrapper) -> Self {
@@ -63,7 +137,7 @@ impl FromIterator<kurbo::Point> for BezWrapper {
         BezWrapper(
             iter.into_iter()
                 .fold(kurbo::BezPath::default(), |mut path, p| {
-                    if path.elements().len() == 0 {
+                    if path.elements().is_empty() {
                         path.move_to(p);
                     } else {
                         path.line_to(p);
@@ -80,9 +154,9 @@ mod tests {
 
     #[test]
     fn test_floor_mul() {
-        let muls = vec![10.0, -10.0, 5.0, 1.0];
-        let inputs = vec![9.0, -13.0, 12.0, 10.0];
-        let expected = vec![0.0, -10.0, 10.0, 10.0];
+        let muls = [10.0, -10.0, 5.0, 1.0];
+        let inputs = [9.0, -13.0, 12.0, 10.0];
+        let expected = [0.0, -10.0, 10.0, 10.0];
         for i in 0..expected.len() {
             assert_eq!(
                 inputs[i].floor_mul(muls[i]),
@@ -96,10 +170,31 @@ mod tests {
         }
     }
     #[test]
+    fn test_monotone() {
+        let pts = [(0.0, 0.0), (1.0, 1.0), (2.0, 1.0), (3.0, 5.0)].map(kurbo::Point::from);
+        let flat = BezWrapper::monotone(&pts).flattened(0.01);
+        assert_eq!(flat.first(), pts.first());
+        assert_eq!(flat.last(), pts.last());
+        // never dips below or rises above the flat segment
+        for p in flat.iter().filter(|p| p.x > 1.0 && p.x < 2.0) {
+            assert!((p.y - 1.0).abs() < 1e-9);
+        }
+        assert!(flat.windows(2).all(|w| w[1].y >= w[0].y));
+    }
+    #[test]
+    fn test_quantile() {
+        let vals = [1.0, 2.0, 3.0, 4.0, 5.0];
+        assert_eq!(quantile(&vals, 0.0), 1.0);
+        assert_eq!(quantile(&vals, 0.5), 3.0);
+        assert_eq!(quantile(&vals, 0.25), 2.0);
+        assert_eq!(quantile(&vals, 1.0), 5.0);
+        assert_eq!(quantile(&[1.0, 2.0], 0.5), 1.5);
+    }
+    #[test]
     fn test_ceil_mul() {
-        let muls = vec![10.0, -10.0, 5.0, 1.0];
-        let inputs = vec![9.0, -13.0, 12.0, 10.0];
-        let expected = vec![10.0, -20.0, 15.0, 10.0];
+        let muls = [10.0, -10.0, 5.0, 1.0];
+        let inputs = [9.0, -13.0, 12.0, 10.0];
+        let expected = [10.0, -20.0, 15.0, 10.0];
         for i in 0..expected.len() {
             assert_eq!(
                 inputs[i].ceil_mul(muls[i]),